@@ -0,0 +1,136 @@
+use std::cmp::min;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::prelude::ThreadRng;
+use rand::seq::SliceRandom;
+
+use crate::error::ApplicationError;
+use crate::state::{Line, State};
+
+/// Files larger than this are skipped, since they are unlikely to be hand-written source code.
+const MAX_FILE_SIZE: u64 = 256 * 1024;
+
+/// How many randomly picked files to try before giving up on finding a snippet with any text.
+const MAX_SNIPPET_ATTEMPTS: usize = 20;
+
+pub struct CodeSnippets {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    snippet_lines: usize,
+    current: Vec<Line>,
+    rng: ThreadRng,
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        // Skip hidden files and directories such as .git
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            // An unreadable directory shouldn't keep the rest of the code from being typed
+            if let Err(e) = collect_files(&path, files) {
+                eprintln!("warning: skipping {}: {}", path.display(), e);
+            }
+        } else if metadata.is_file() && metadata.len() <= MAX_FILE_SIZE {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Removes the indentation shared by every line, so that snippets taken from deeply nested code
+/// don't start halfway across the screen.
+fn dedent(lines: &mut [Line]) {
+    let common_indent = lines
+        .iter()
+        .filter(|line| !line.words.is_empty())
+        .map(|line| line.indent.chars().count())
+        .min()
+        .unwrap_or_default();
+
+    for line in lines {
+        line.indent = line.indent.chars().skip(common_indent).collect();
+    }
+}
+
+impl CodeSnippets {
+    pub fn from_dir<P: AsRef<Path>>(
+        dir: P,
+        snippet_lines: usize,
+    ) -> Result<Self, ApplicationError> {
+        let dir = dir.as_ref().to_path_buf();
+        let mut files = Vec::new();
+        collect_files(&dir, &mut files).map_err(ApplicationError::CodeLoad)?;
+
+        if files.is_empty() {
+            return Err(ApplicationError::NoCodeSnippets(dir));
+        }
+
+        Ok(Self {
+            dir,
+            files,
+            snippet_lines,
+            current: Vec::new(),
+            rng: rand::thread_rng(),
+        })
+    }
+
+    fn random_snippet(&mut self) -> Option<Vec<Line>> {
+        for _ in 0..MAX_SNIPPET_ATTEMPTS {
+            let path = self.files.choose(&mut self.rng)?;
+
+            // Binary and non-UTF-8 files are skipped
+            let contents = match fs::read_to_string(path) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+
+            let source_lines: Vec<&str> = contents.lines().collect();
+            let start_candidates: Vec<usize> = (0..source_lines.len())
+                .filter(|&i| !source_lines[i].trim().is_empty())
+                .collect();
+            let start = match start_candidates.choose(&mut self.rng) {
+                Some(&start) => start,
+                None => continue,
+            };
+            let end = min(start + self.snippet_lines, source_lines.len());
+
+            let mut snippet: Vec<Line> = source_lines[start..end]
+                .iter()
+                .map(|source_line| Line::from_source(source_line))
+                .collect();
+            while snippet.last().is_some_and(|line| line.words.is_empty()) {
+                snippet.pop();
+            }
+            dedent(&mut snippet);
+
+            return Some(snippet);
+        }
+        None
+    }
+
+    /// Picks the snippet for the next test. When `repeat_text` is set, the previous snippet is
+    /// kept.
+    pub fn start_test(&mut self, repeat_text: bool) -> Result<(), ApplicationError> {
+        if !repeat_text || self.current.is_empty() {
+            self.current = self
+                .random_snippet()
+                .ok_or_else(|| ApplicationError::NoCodeSnippets(self.dir.clone()))?;
+        }
+        Ok(())
+    }
+
+    pub fn load_text(&self, state: &mut State) {
+        if state.lines.is_empty() {
+            state.lines = self.current.clone();
+        }
+    }
+}
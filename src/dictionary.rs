@@ -1,48 +1,459 @@
+use std::collections::{HashSet, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use rand::prelude::ThreadRng;
+use rand::distributions::WeightedIndex;
+use rand::prelude::{Distribution, ThreadRng};
 use rand::{self, Rng};
 
 use crate::error::ApplicationError;
-use crate::state::State;
+use crate::opt::DictionarySource;
+use crate::pseudo::{letter_word, MarkovChain};
+use crate::state::{Line, State};
+
+/// Fraction of words that come from the practice words, when there are any.
+const PRACTICE_WORD_RATIO: f64 = 0.3;
+
+/// Fraction of n-gram drill items that repeat the bare n-gram rather than use a word containing it.
+const NGRAM_REPETITION_RATIO: f64 = 0.25;
+
+/// How many times in a row a bare n-gram is repeated.
+const NGRAM_REPETITIONS: usize = 3;
+
+/// With fewer real words than this made of the allowed letters, some generated words are mixed
+/// in to keep the text varied.
+const MIN_LETTER_WORDS: usize = 30;
+
+/// The order words are handed out in.
+enum WordOrder {
+    /// Words are picked at random, without end, first choosing a list by weight and then a word
+    /// from it.
+    Random {
+        weights: Vec<u32>,
+        list_weights: WeightedIndex<u32>,
+    },
+    /// Words of the single list are handed out once each in the order they appear, starting over
+    /// with every test.
+    Sequential { next: usize },
+}
+
+/// Lines starting with this are comments rather than words.
+const COMMENT_PREFIX: char = '#';
+
+/// Reads the words of a word list with a length within the bounds, leaving out any that are
+/// already in `seen`. Surrounding whitespace is trimmed, and blank and comment lines are skipped.
+fn load_word_list<P: AsRef<Path>>(
+    path: P,
+    min_word_len: usize,
+    max_word_len: usize,
+    seen: &mut HashSet<String>,
+) -> Result<Vec<String>, ApplicationError> {
+    let path = path.as_ref();
+    if min_word_len > max_word_len {
+        return Err(ApplicationError::WordLengthBounds(
+            min_word_len,
+            max_word_len,
+        ));
+    }
+
+    let f = File::open(path).map_err(|e| ApplicationError::DictionaryLoad(path.into(), e))?;
+    let reader = BufReader::new(f);
+
+    let mut words = Vec::new();
+    for (index, line) in reader.split(b'\n').enumerate() {
+        let line = line.map_err(|e| ApplicationError::DictionaryLoad(path.into(), e))?;
+        let line = String::from_utf8(line)
+            .map_err(|_| ApplicationError::DictionaryEncoding(path.into(), index + 1))?;
+
+        let word = line.trim();
+        if word.is_empty() || word.starts_with(COMMENT_PREFIX) {
+            continue;
+        }
+
+        let len = word.chars().count();
+        if min_word_len <= len && len <= max_word_len && seen.insert(word.to_string()) {
+            words.push(word.to_string());
+        }
+    }
+
+    if words.is_empty() {
+        return Err(ApplicationError::DictionaryEmpty(
+            path.into(),
+            min_word_len,
+            max_word_len,
+        ));
+    }
+    Ok(words)
+}
+
+/// Reads every word in a word list, with no length limits.
+pub fn read_word_list<P: AsRef<Path>>(path: P) -> Result<Vec<String>, ApplicationError> {
+    load_word_list(path, 0, usize::MAX, &mut HashSet::new())
+}
+
+/// Whether every letter of `word` is one of `letters`, ignoring case.
+fn made_of_letters(word: &str, letters: &[char]) -> bool {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| letters.contains(&c))
+}
+
+/// The words of each list made only of the allowed letters.
+struct LetterFilter {
+    letters: Vec<char>,
+    lists: Vec<Vec<String>>,
+    /// `None` when no list has any matching words.
+    list_weights: Option<WeightedIndex<u32>>,
+    word_count: usize,
+}
+
+/// The n-grams being drilled, each with the words that contain it.
+struct NgramDrill {
+    ngrams: Vec<String>,
+    words: Vec<Vec<String>>,
+    /// Further repetitions of the last bare n-gram handed out.
+    repetitions: VecDeque<String>,
+}
+
+/// Made up words generated alongside the real ones, with a chain trained on each word list so
+/// that the made up words follow the letters and lengths of the list they stand in for.
+struct PseudoWords {
+    chains: Vec<MarkovChain>,
+    /// Fraction of words that are made up.
+    ratio: f64,
+}
 
 pub struct Dictionary {
-    words: Vec<String>,
+    lists: Vec<Vec<String>>,
+    order: WordOrder,
+    min_word_len: usize,
+    max_word_len: usize,
+    /// The length bounds each list was loaded with.
+    list_word_lens: Vec<(usize, usize)>,
+    letter_filter: Option<LetterFilter>,
+    ngram_drill: Option<NgramDrill>,
+    pseudo_words: Option<PseudoWords>,
     rng: ThreadRng,
+    generated: Vec<String>,
+    replay: VecDeque<String>,
+    practice_words: Vec<String>,
+    practice_weights: Option<WeightedIndex<f64>>,
 }
 
 impl Dictionary {
-    pub fn from_file<P: AsRef<Path>>(
-        path: P,
+    /// Loads every word list, applying each list's own length bounds where it has them and the
+    /// given ones otherwise. A word that appears in several lists is only kept in the first.
+    pub fn from_sources(
+        sources: &[DictionarySource],
         min_word_len: usize,
         max_word_len: usize,
     ) -> Result<Self, ApplicationError> {
-        let f = File::open(path).map_err(ApplicationError::DictionaryLoad)?;
-        let reader = BufReader::new(f);
+        let list_word_lens: Vec<(usize, usize)> = sources
+            .iter()
+            .map(|source| {
+                (
+                    source.min_word_len.unwrap_or(min_word_len),
+                    source.max_word_len.unwrap_or(max_word_len),
+                )
+            })
+            .collect();
+        let mut seen = HashSet::new();
+        let lists = sources
+            .iter()
+            .zip(&list_word_lens)
+            .map(|(source, &(min, max))| load_word_list(&source.path, min, max, &mut seen))
+            .collect::<Result<Vec<_>, _>>()?;
+        let weights: Vec<u32> = sources.iter().map(|source| source.weight).collect();
+        let list_weights =
+            WeightedIndex::new(&weights).map_err(ApplicationError::DictionaryWeights)?;
+
+        Ok(Self {
+            lists,
+            order: WordOrder::Random {
+                weights,
+                list_weights,
+            },
+            min_word_len,
+            max_word_len,
+            list_word_lens,
+            letter_filter: None,
+            ngram_drill: None,
+            pseudo_words: None,
+            rng: rand::thread_rng(),
+            generated: Vec::new(),
+            replay: VecDeque::new(),
+            practice_words: Vec::new(),
+            practice_weights: None,
+        })
+    }
+
+    /// A dictionary that hands out the words of `text` in order, for typing a specific text.
+    pub fn from_text(text: &str) -> Result<Self, ApplicationError> {
+        let words: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+        if words.is_empty() {
+            return Err(ApplicationError::EmptyText);
+        }
+
         Ok(Self {
-            words: reader
-                .lines()
-                .filter_map(|line| match line {
-                    Ok(l) if min_word_len <= l.len() && l.len() <= max_word_len => Some(l),
-                    Ok(_) => None,
-                    Err(_) => None,
-                })
-                .collect(),
+            lists: vec![words],
+            order: WordOrder::Sequential { next: 0 },
+            min_word_len: 0,
+            max_word_len: 0,
+            list_word_lens: Vec::new(),
+            letter_filter: None,
+            ngram_drill: None,
+            pseudo_words: None,
             rng: rand::thread_rng(),
+            generated: Vec::new(),
+            replay: VecDeque::new(),
+            practice_words: Vec::new(),
+            practice_weights: None,
         })
     }
 
+    /// Whether words are picked at random from word lists, rather than taken in order from a text.
+    pub fn is_random(&self) -> bool {
+        matches!(self.order, WordOrder::Random { .. })
+    }
+
+    /// Prepares for a new test. When `repeat_text` is set, the words handed out for the previous
+    /// test are handed out again in the same order before any new random words.
+    pub fn start_test(&mut self, repeat_text: bool) {
+        let previous = std::mem::take(&mut self.generated);
+        self.replay = if repeat_text {
+            previous.into()
+        } else {
+            VecDeque::new()
+        };
+        if let WordOrder::Sequential { next } = &mut self.order {
+            *next = 0;
+        }
+    }
+
+    /// Sets words to mix in with the random ones, each paired with how likely it is to be picked
+    /// relative to the others.
+    pub fn set_practice_words(&mut self, weighted_words: Vec<(String, f64)>) {
+        let (words, weights): (Vec<_>, Vec<_>) = weighted_words.into_iter().unzip();
+        // Fails when there are no words or no weight is positive, in which case nothing is mixed in
+        self.practice_weights = WeightedIndex::new(weights).ok();
+        self.practice_words = words;
+    }
+
+    /// A practice word to mix in, if one comes up. Words using letters that aren't unlocked yet are
+    /// left out.
+    fn get_practice_word(&mut self) -> Option<String> {
+        let weights = self.practice_weights.as_ref()?;
+        if !self.rng.gen_bool(PRACTICE_WORD_RATIO) {
+            return None;
+        }
+        let word = &self.practice_words[weights.sample(&mut self.rng)];
+        if let Some(filter) = &self.letter_filter {
+            if !made_of_letters(word, &filter.letters) {
+                return None;
+            }
+        }
+        Some(word.clone())
+    }
+
+    /// Restricts random words to the ones made only of `letters`.
+    pub fn set_allowed_letters(&mut self, letters: &[char]) {
+        let weights = match &self.order {
+            WordOrder::Random { weights, .. } => weights,
+            WordOrder::Sequential { .. } => return,
+        };
+        let letters = letters.to_vec();
+
+        let lists: Vec<Vec<String>> = self
+            .lists
+            .iter()
+            .map(|words| {
+                words
+                    .iter()
+                    .filter(|word| made_of_letters(word, &letters))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+        let list_weights = WeightedIndex::new(lists.iter().zip(weights).map(|(words, &weight)| {
+            if words.is_empty() {
+                0
+            } else {
+                weight
+            }
+        }))
+        .ok();
+        let word_count = lists.iter().map(Vec::len).sum();
+
+        self.letter_filter = Some(LetterFilter {
+            letters,
+            lists,
+            list_weights,
+            word_count,
+        });
+    }
+
+    /// A random word made only of the allowed letters. Generated words stand in for some or all
+    /// of the real ones when too few real words match.
+    fn get_letter_word(&mut self) -> Option<String> {
+        let filter = self.letter_filter.as_ref()?;
+
+        let real_word_ratio = filter.word_count as f64 / MIN_LETTER_WORDS as f64;
+        let list_weights = match &filter.list_weights {
+            Some(list_weights) if self.rng.gen_bool(real_word_ratio.min(1.0)) => list_weights,
+            _ => {
+                return Some(letter_word(
+                    &mut self.rng,
+                    &filter.letters,
+                    self.min_word_len,
+                    self.max_word_len,
+                ))
+            }
+        };
+
+        let words = &filter.lists[list_weights.sample(&mut self.rng)];
+        Some(words[self.rng.gen_range(0..words.len())].clone())
+    }
+
+    /// Drills the given n-grams with words that contain them and repetitions of the n-grams
+    /// themselves, or stops drilling n-grams.
+    pub fn set_ngrams(&mut self, ngrams: Option<Vec<String>>) {
+        let ngrams = match (&self.order, ngrams) {
+            (WordOrder::Random { .. }, Some(ngrams)) if !ngrams.is_empty() => ngrams,
+            _ => {
+                self.ngram_drill = None;
+                return;
+            }
+        };
+
+        let words = ngrams
+            .iter()
+            .map(|ngram| {
+                self.lists
+                    .iter()
+                    .flatten()
+                    .filter(|word| word.to_lowercase().contains(ngram.as_str()))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+
+        self.ngram_drill = Some(NgramDrill {
+            ngrams,
+            words,
+            repetitions: VecDeque::new(),
+        });
+    }
+
+    fn get_ngram_word(&mut self) -> Option<String> {
+        let drill = self.ngram_drill.as_mut()?;
+        if let Some(repetition) = drill.repetitions.pop_front() {
+            return Some(repetition);
+        }
+
+        let index = self.rng.gen_range(0..drill.ngrams.len());
+        let words = &drill.words[index];
+        if words.is_empty() || self.rng.gen_bool(NGRAM_REPETITION_RATIO) {
+            let ngram = drill.ngrams[index].clone();
+            drill
+                .repetitions
+                .extend(std::iter::repeat_n(ngram.clone(), NGRAM_REPETITIONS - 1));
+            return Some(ngram);
+        }
+        Some(words[self.rng.gen_range(0..words.len())].clone())
+    }
+
+    /// Mixes made up words trained on the word lists in with the real words. `ratio` is the
+    /// fraction of words that are made up, and 0 turns them off.
+    pub fn set_pseudo_word_ratio(&mut self, ratio: f64) {
+        self.pseudo_words = match self.order {
+            WordOrder::Random { .. } if ratio > 0.0 => Some(PseudoWords {
+                chains: self.lists.iter().map(MarkovChain::train).collect(),
+                ratio: ratio.min(1.0),
+            }),
+            _ => None,
+        };
+    }
+
+    /// A made up word in place of a word from a randomly picked list, within that list's length
+    /// bounds.
+    fn get_pseudo_word(&mut self) -> Option<String> {
+        let pseudo_words = self.pseudo_words.as_ref()?;
+        let list_weights = match &self.order {
+            WordOrder::Random { list_weights, .. } => list_weights,
+            WordOrder::Sequential { .. } => return None,
+        };
+        if !self.rng.gen_bool(pseudo_words.ratio) {
+            return None;
+        }
+        let list_index = list_weights.sample(&mut self.rng);
+        let (min_word_len, max_word_len) = self.list_word_lens[list_index];
+        pseudo_words.chains[list_index].generate(&mut self.rng, min_word_len, max_word_len)
+    }
+
     pub fn get_random_word(&mut self) -> String {
-        let rand_n = self.rng.gen_range(0..self.words.len());
-        self.words[rand_n].clone()
+        if let Some(word) = self.get_ngram_word() {
+            return word;
+        }
+        if let Some(word) = self.get_letter_word() {
+            return word;
+        }
+        if let Some(word) = self.get_pseudo_word() {
+            return word;
+        }
+
+        let list_index = match &self.order {
+            WordOrder::Random { list_weights, .. } => list_weights.sample(&mut self.rng),
+            WordOrder::Sequential { .. } => 0,
+        };
+        let words = &self.lists[list_index];
+        let rand_n = self.rng.gen_range(0..words.len());
+        words[rand_n].clone()
+    }
+
+    fn get_sequential_word(&mut self) -> Option<String> {
+        match &mut self.order {
+            WordOrder::Random { .. } => None,
+            WordOrder::Sequential { next } => {
+                let word = self.lists[0].get(*next)?.clone();
+                *next += 1;
+                Some(word)
+            }
+        }
+    }
+
+    /// The next word of the text, or `None` once a sequential text has run out.
+    fn next_word(&mut self) -> Option<String> {
+        let word = match (self.replay.pop_front(), &self.order) {
+            (Some(word), _) => word,
+            (None, WordOrder::Sequential { .. }) => self.get_sequential_word()?,
+            (None, WordOrder::Random { .. }) => match self.get_practice_word() {
+                Some(word) => word,
+                None => self.get_random_word(),
+            },
+        };
+        self.generated.push(word.clone());
+        Some(word)
     }
 
-    pub fn load_words(&mut self, state: &mut State, num_words: usize) {
-        let text = &mut state.all_words;
-        while text.len() < num_words {
-            text.push(self.get_random_word());
+    /// Adds words to the end of the text until there are `num_words_ahead` words after the ones
+    /// already typed, or the words run out.
+    pub fn load_words(&mut self, state: &mut State, num_words_ahead: usize) {
+        let num_words = state.typed_words.len() + num_words_ahead;
+        let mut missing_words = num_words.saturating_sub(state.word_count());
+        if state.lines.is_empty() {
+            state.lines.push(Line::default());
+        }
+
+        while missing_words > 0 {
+            let word = match self.next_word() {
+                Some(word) => word,
+                None => break,
+            };
+            if let Some(line) = state.lines.last_mut() {
+                line.words.push(word);
+            }
+            missing_words -= 1;
         }
     }
 }
@@ -0,0 +1,85 @@
+use crate::stats::Stats;
+
+/// English letters from most to least common, the default order letters are unlocked in.
+pub const DEFAULT_UNLOCK_ORDER: &str = "etaoinsrhldcumfpgwybvkxjqz";
+
+/// A drill that only uses a set of letters, adding another one each time a test is good enough.
+pub struct LetterDrill {
+    letters: Vec<char>,
+    locked: Vec<char>,
+    min_accuracy: f64,
+    min_wpm: f64,
+    just_unlocked: Option<char>,
+}
+
+impl LetterDrill {
+    /// `min_accuracy` is a percentage.
+    pub fn new(letters: &str, unlock_order: &str, min_accuracy: f64, min_wpm: f64) -> Self {
+        let mut unlocked = Vec::new();
+        for c in letters
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+        {
+            if !unlocked.contains(&c) {
+                unlocked.push(c);
+            }
+        }
+
+        let mut locked = Vec::new();
+        for c in unlock_order.chars().flat_map(char::to_lowercase) {
+            if !unlocked.contains(&c) && !locked.contains(&c) {
+                locked.push(c);
+            }
+        }
+
+        Self {
+            letters: unlocked,
+            locked,
+            min_accuracy: min_accuracy / 100.0,
+            min_wpm,
+            just_unlocked: None,
+        }
+    }
+
+    pub fn letters(&self) -> &[char] {
+        &self.letters
+    }
+
+    pub fn next_letter(&self) -> Option<char> {
+        self.locked.first().copied()
+    }
+
+    /// The letter unlocked by the last test, if it was good enough.
+    pub fn just_unlocked(&self) -> Option<char> {
+        self.just_unlocked
+    }
+
+    pub fn min_accuracy(&self) -> f64 {
+        self.min_accuracy
+    }
+
+    pub fn min_wpm(&self) -> f64 {
+        self.min_wpm
+    }
+
+    /// Unlocks the next letter if the test met both thresholds. Returns whether the letters
+    /// changed.
+    pub fn record_result(&mut self, stats: &Stats) -> bool {
+        let passed = stats.attempted_word_count > 0
+            && stats
+                .accuracy
+                .is_some_and(|accuracy| accuracy >= self.min_accuracy)
+            && stats.net_wpm >= self.min_wpm;
+
+        self.just_unlocked = if passed && !self.locked.is_empty() {
+            Some(self.locked.remove(0))
+        } else {
+            None
+        };
+        if let Some(c) = self.just_unlocked {
+            self.letters.push(c);
+        }
+        self.just_unlocked.is_some()
+    }
+}
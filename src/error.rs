@@ -1,12 +1,52 @@
-use std::{io, sync::mpsc::SendError};
+use std::{io, path::PathBuf, sync::mpsc::SendError};
 
+use rand::distributions::WeightedError;
 use termion::event::Key;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ApplicationError {
-    #[error("dictionary loading failed")]
-    DictionaryLoad(#[source] io::Error),
+    #[error("code snippet loading failed")]
+    CodeLoad(#[source] io::Error),
+
+    #[error("failed to read corpus {0}")]
+    CorpusLoad(PathBuf, #[source] io::Error),
+
+    #[error("failed to parse data file {0}")]
+    DataParse(PathBuf, #[source] serde_json::Error),
+
+    #[error("failed to read data file {0}")]
+    DataRead(PathBuf, #[source] io::Error),
+
+    #[error("failed to serialize data for {0}")]
+    DataSerialize(PathBuf, #[source] serde_json::Error),
+
+    #[error("failed to write data file {0}")]
+    DataWrite(PathBuf, #[source] io::Error),
+
+    #[error("no words in dictionary {0} with a length from {1} to {2}, after skipping comments and duplicates")]
+    DictionaryEmpty(PathBuf, usize, usize),
+
+    #[error("invalid UTF-8 in dictionary {0} at line {1}")]
+    DictionaryEncoding(PathBuf, usize),
+
+    #[error("no words in dictionary {0} made only of the letters {1}")]
+    DictionaryLettersEmpty(PathBuf, String),
+
+    #[error("failed to load dictionary {0}")]
+    DictionaryLoad(PathBuf, #[source] io::Error),
+
+    #[error("invalid dictionary weights")]
+    DictionaryWeights(#[source] WeightedError),
+
+    #[error("no letters given for the letter drill")]
+    DrillLettersEmpty,
+
+    #[error("the text to type is empty")]
+    EmptyText,
+
+    #[error("failed to write export to {0}")]
+    ExportWrite(PathBuf, #[source] io::Error),
 
     #[error("failed getting key input")]
     InputKey(#[source] io::Error),
@@ -14,9 +54,27 @@ pub enum ApplicationError {
     #[error("failed to send key input on channel")]
     InputSend(#[source] SendError<Key>),
 
+    #[error("failed to serialize output as JSON")]
+    JsonSerialize(#[source] serde_json::Error),
+
+    #[error("invalid keyboard layout {0}: {1}")]
+    LayoutInvalid(PathBuf, String),
+
+    #[error("failed to load keyboard layout {0}")]
+    LayoutLoad(PathBuf, #[source] io::Error),
+
+    #[error("failed to parse keyboard layout {0}")]
+    LayoutParse(PathBuf, #[source] toml::de::Error),
+
+    #[error("no source files with text to type found in {0}")]
+    NoCodeSnippets(PathBuf),
+
     #[error("failed to switch to raw mode output")]
     RawMode(#[source] io::Error),
 
+    #[error("failed to point standard output at the terminal")]
+    StdoutRedirect(#[source] io::Error),
+
     #[error("failed to clear terminal")]
     TerminalClear(#[source] io::Error),
 
@@ -25,4 +83,19 @@ pub enum ApplicationError {
 
     #[error("failed to instantiate terminal object")]
     TerminalInstantiation(#[source] io::Error),
+
+    #[error("failed to read the text to type")]
+    TextLoad(#[source] io::Error),
+
+    #[error("failed to open the terminal")]
+    TtyOpen(#[source] io::Error),
+
+    #[error("UTC offset {0} is not between -12 and 14 hours")]
+    UtcOffsetInvalid(f64),
+
+    #[error("minimum word length {0} is greater than the maximum {1}")]
+    WordLengthBounds(usize, usize),
+
+    #[error("failed to write word list {0}")]
+    WordListWrite(PathBuf, #[source] io::Error),
 }
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApplicationError;
+use crate::keyboard::{Finger, Hand, KeyboardLayout};
+use crate::ngram::ngram_latencies;
+use crate::state::{Keystroke, IDLE_THRESHOLD};
+use crate::storage::{read_json, write_json};
+
+/// Keys that are at least this many rows apart need the hand to stretch.
+const ROW_JUMP_DISTANCE: usize = 2;
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct FingerCounts {
+    pub keystrokes: u64,
+    pub errors: u64,
+    /// Total milliseconds since the previous keystroke, over the keystrokes counted in
+    /// `intervals`.
+    pub interval_ms: u64,
+    pub intervals: u64,
+}
+
+impl FingerCounts {
+    pub fn error_rate(&self) -> Option<f64> {
+        if self.keystrokes == 0 {
+            return None;
+        }
+        Some(self.errors as f64 / self.keystrokes as f64)
+    }
+
+    /// Average time taken to press a key with this finger.
+    pub fn average_interval(&self) -> Option<Duration> {
+        if self.intervals == 0 {
+            return None;
+        }
+        Some(Duration::from_millis(self.interval_ms / self.intervals))
+    }
+}
+
+impl AddAssign for FingerCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.keystrokes += other.keystrokes;
+        self.errors += other.errors;
+        self.interval_ms += other.interval_ms;
+        self.intervals += other.intervals;
+    }
+}
+
+/// How the keystrokes of one or more tests were spread over the fingers of a layout.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FingerUsage {
+    /// Indexed like `Finger::ALL`.
+    pub fingers: [FingerCounts; 9],
+    pub bigram_count: u64,
+    /// Consecutive keys pressed by the same finger, not counting a key pressed twice.
+    pub same_finger_bigrams: u64,
+    /// Consecutive keys on the same hand that are two or more rows apart.
+    pub row_jumps: u64,
+}
+
+impl FingerUsage {
+    pub fn from_keystrokes(keystrokes: &[Keystroke], layout: &KeyboardLayout) -> Self {
+        let mut usage = Self::default();
+
+        let mut previous: Option<&Keystroke> = None;
+        for keystroke in keystrokes {
+            // Mistakes are put down to the finger that should have pressed the key
+            let c = keystroke.expected.unwrap_or(keystroke.typed);
+            if let Some(position) = layout.position(c) {
+                let counts = &mut usage.fingers[Self::index(layout.finger(position))];
+                counts.keystrokes += 1;
+                if !keystroke.is_correct() {
+                    counts.errors += 1;
+                }
+
+                if let Some(previous) = previous {
+                    let interval = keystroke.elapsed - previous.elapsed;
+                    if keystroke.is_correct()
+                        && !keystroke.after_correction
+                        && interval <= IDLE_THRESHOLD
+                    {
+                        counts.interval_ms += interval.as_millis() as u64;
+                        counts.intervals += 1;
+                    }
+                }
+            }
+            previous = Some(keystroke);
+        }
+
+        for (bigram, _) in ngram_latencies(keystrokes, 2) {
+            let mut chars = bigram.chars();
+            let positions = (
+                chars.next().and_then(|c| layout.position(c)),
+                chars.next().and_then(|c| layout.position(c)),
+            );
+            let (first, second) = match positions {
+                (Some(first), Some(second)) => (first, second),
+                _ => continue,
+            };
+
+            usage.bigram_count += 1;
+            let (first_finger, second_finger) = (layout.finger(first), layout.finger(second));
+            if first_finger == second_finger && !first.same_key(second) {
+                usage.same_finger_bigrams += 1;
+            }
+            if first_finger.hand().is_some()
+                && first_finger.hand() == second_finger.hand()
+                && first.row().abs_diff(second.row()) >= ROW_JUMP_DISTANCE
+            {
+                usage.row_jumps += 1;
+            }
+        }
+
+        usage
+    }
+
+    fn index(finger: Finger) -> usize {
+        Finger::ALL
+            .iter()
+            .position(|&other| other == finger)
+            .unwrap_or_default()
+    }
+
+    pub fn finger(&self, finger: Finger) -> &FingerCounts {
+        &self.fingers[Self::index(finger)]
+    }
+
+    /// The counts of every finger on one hand added together.
+    pub fn hand(&self, hand: Hand) -> FingerCounts {
+        let mut total = FingerCounts::default();
+        for finger in Finger::ALL
+            .iter()
+            .filter(|finger| finger.hand() == Some(hand))
+        {
+            total += *self.finger(*finger);
+        }
+        total
+    }
+
+    pub fn keystroke_count(&self) -> u64 {
+        self.fingers.iter().map(|counts| counts.keystrokes).sum()
+    }
+
+    /// The part of all keystrokes made with the given counts.
+    pub fn share(&self, counts: &FingerCounts) -> f64 {
+        match self.keystroke_count() {
+            0 => 0.0,
+            total => counts.keystrokes as f64 / total as f64,
+        }
+    }
+
+    pub fn same_finger_share(&self) -> Option<f64> {
+        if self.bigram_count == 0 {
+            return None;
+        }
+        Some(self.same_finger_bigrams as f64 / self.bigram_count as f64)
+    }
+}
+
+/// One row of a finger or hand table: the name, the share of keystrokes, the error rate and the
+/// average time per key.
+pub fn usage_row(name: &str, usage: &FingerUsage, counts: &FingerCounts) -> String {
+    format!(
+        "{:<10} {:>4.0}% {:>6} {:>6}",
+        name,
+        usage.share(counts) * 100.0,
+        counts
+            .error_rate()
+            .map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0)),
+        counts
+            .average_interval()
+            .map_or("-".to_string(), |interval| format!(
+                "{}ms",
+                interval.as_millis()
+            )),
+    )
+}
+
+pub fn usage_header() -> String {
+    format!("{:<10} {:>5} {:>6} {:>6}", "", "keys", "errors", "speed")
+}
+
+/// Every row of the finger table followed by the hand totals, leaving out unused fingers.
+pub fn usage_rows(usage: &FingerUsage) -> Vec<String> {
+    let fingers = Finger::ALL
+        .iter()
+        .filter(|&&finger| usage.finger(finger).keystrokes > 0)
+        .map(|&finger| usage_row(finger.short_name(), usage, usage.finger(finger)));
+    let hands = Hand::ALL
+        .iter()
+        .map(|&hand| usage_row(hand.name(), usage, &usage.hand(hand)));
+    fingers.chain(hands).collect()
+}
+
+impl AddAssign<&FingerUsage> for FingerUsage {
+    fn add_assign(&mut self, other: &FingerUsage) {
+        for (counts, other_counts) in self.fingers.iter_mut().zip(other.fingers.iter()) {
+            *counts += *other_counts;
+        }
+        self.bigram_count += other.bigram_count;
+        self.same_finger_bigrams += other.same_finger_bigrams;
+        self.row_jumps += other.row_jumps;
+    }
+}
+
+/// Finger usage added up over every test, kept across sessions. Layouts put keys under different
+/// fingers, so each layout is counted separately.
+pub struct FingerHistory {
+    path: PathBuf,
+    layouts: HashMap<String, FingerUsage>,
+}
+
+impl FingerHistory {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApplicationError> {
+        let path = path.as_ref().to_path_buf();
+        let layouts = read_json(&path)?.unwrap_or_default();
+        Ok(Self { path, layouts })
+    }
+
+    pub fn save(&self) -> Result<(), ApplicationError> {
+        write_json(&self.path, &self.layouts)
+    }
+
+    pub fn record(&mut self, layout: &KeyboardLayout, usage: &FingerUsage) {
+        *self.layouts.entry(layout.name.clone()).or_default() += usage;
+    }
+
+    pub fn usage(&self, layout: &KeyboardLayout) -> Option<&FingerUsage> {
+        self.layouts.get(&layout.name)
+    }
+}
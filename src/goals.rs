@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApplicationError;
+use crate::history::{Run, RunHistory};
+use crate::opt::CliOptions;
+use crate::storage::{data_dir, now_timestamp, read_json, write_json, GOALS_FILE, RUNS_FILE};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const SECONDS_PER_HOUR: f64 = 60.0 * 60.0;
+/// Offsets of the time zones furthest behind and ahead of UTC.
+const UTC_OFFSET_HOURS_RANGE: (f64, f64) = (-12.0, 14.0);
+
+/// Practice to do every day. Goals that are `None` aren't tracked.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DailyGoals {
+    pub minutes: Option<f64>,
+    pub tests: Option<usize>,
+    /// WPM to reach in at least one test.
+    pub wpm: Option<f64>,
+    /// Hours ahead of UTC that days start at, so that they follow the local midnight.
+    #[serde(default)]
+    pub utc_offset_hours: f64,
+}
+
+impl DailyGoals {
+    pub fn load(path: &Path) -> Result<Self, ApplicationError> {
+        Ok(read_json(path)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ApplicationError> {
+        write_json(path, self)
+    }
+
+    fn utc_offset_secs(&self) -> i64 {
+        (self.utc_offset_hours * SECONDS_PER_HOUR).round() as i64
+    }
+
+    /// The day a timestamp falls on, counted since the Unix epoch with days starting at the
+    /// local midnight.
+    fn day_of(&self, timestamp: u64) -> i64 {
+        (timestamp as i64 + self.utc_offset_secs()).div_euclid(SECONDS_PER_DAY)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.minutes.is_none() && self.tests.is_none() && self.wpm.is_none()
+    }
+
+    fn met(&self, day: &DayProgress) -> bool {
+        !self.is_empty()
+            && self.minutes.is_none_or(|minutes| day.minutes >= minutes)
+            && self.tests.is_none_or(|tests| day.tests >= tests)
+            && self
+                .wpm
+                .is_none_or(|wpm| day.best_wpm.is_some_and(|best| best >= wpm))
+    }
+}
+
+/// What was typed over one day.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DayProgress {
+    pub minutes: f64,
+    pub tests: usize,
+    pub best_wpm: Option<f64>,
+}
+
+impl DayProgress {
+    fn add(&mut self, run: &Run) {
+        self.minutes += run.duration_secs / 60.0;
+        self.tests += 1;
+        self.best_wpm = Some(
+            self.best_wpm
+                .map_or(run.net_wpm, |best| best.max(run.net_wpm)),
+        );
+    }
+}
+
+/// Progress towards the daily goals today, along with streaks of days they were met.
+pub struct GoalProgress {
+    pub goals: DailyGoals,
+    pub today: DayProgress,
+    /// Days in a row the goals were met, up to today. Today only breaks the streak once it's over.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+impl GoalProgress {
+    /// Works out the progress as of `now`, in seconds since the Unix epoch.
+    pub fn new(goals: DailyGoals, runs: &[Run], now: u64) -> Self {
+        let today = goals.day_of(now);
+
+        let mut days: HashMap<i64, DayProgress> = HashMap::new();
+        for run in runs {
+            days.entry(goals.day_of(run.timestamp))
+                .or_default()
+                .add(run);
+        }
+        let met = |day: i64| days.get(&day).is_some_and(|progress| goals.met(progress));
+
+        let mut current_streak = 0;
+        let mut day = if met(today) { today } else { today - 1 };
+        while met(day) {
+            current_streak += 1;
+            day -= 1;
+        }
+
+        let mut longest_streak = 0;
+        let mut streak = 0;
+        let first_day = days.keys().copied().min().unwrap_or(today);
+        for day in first_day..=today {
+            if met(day) {
+                streak += 1;
+                longest_streak = longest_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+        }
+
+        Self {
+            today: days.get(&today).copied().unwrap_or_default(),
+            goals,
+            current_streak,
+            longest_streak,
+        }
+    }
+
+    pub fn met_today(&self) -> bool {
+        self.goals.met(&self.today)
+    }
+
+    /// A line for each goal with today's progress towards it, and whether it has been reached.
+    pub fn lines(&self) -> Vec<(String, bool)> {
+        let mut lines = Vec::new();
+        if let Some(minutes) = self.goals.minutes {
+            lines.push((
+                format!("Minutes today: {:.1}/{}", self.today.minutes, minutes),
+                self.today.minutes >= minutes,
+            ));
+        }
+        if let Some(tests) = self.goals.tests {
+            lines.push((
+                format!("Tests today: {}/{}", self.today.tests, tests),
+                self.today.tests >= tests,
+            ));
+        }
+        if let Some(wpm) = self.goals.wpm {
+            let best = self.today.best_wpm.unwrap_or_default();
+            lines.push((format!("Best WPM today: {:.1}/{}", best, wpm), best >= wpm));
+        }
+        lines
+    }
+}
+
+/// Loads the daily goals and works out progress from the run history, unless no goals are set.
+pub fn load_goal_progress(
+    opt: &CliOptions,
+    history: &RunHistory,
+) -> Result<Option<GoalProgress>, ApplicationError> {
+    let goals = DailyGoals::load(&data_dir(opt).join(GOALS_FILE))?;
+    if goals.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(GoalProgress::new(
+        goals,
+        &history.runs,
+        now_timestamp(),
+    )))
+}
+
+pub fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Updates any goals given on the command line, then prints today's progress and streaks.
+pub fn run_goals_command(
+    minutes: Option<f64>,
+    tests: Option<usize>,
+    wpm: Option<f64>,
+    clear: bool,
+    utc_offset: Option<f64>,
+    opt: &CliOptions,
+) -> Result<(), ApplicationError> {
+    let path = data_dir(opt).join(GOALS_FILE);
+    let mut goals = DailyGoals::load(&path)?;
+
+    if let Some(hours) = utc_offset {
+        let (min, max) = UTC_OFFSET_HOURS_RANGE;
+        if !(min..=max).contains(&hours) {
+            return Err(ApplicationError::UtcOffsetInvalid(hours));
+        }
+        goals.utc_offset_hours = hours;
+    }
+    if clear {
+        // The offset isn't a goal, so it's kept
+        goals = DailyGoals {
+            utc_offset_hours: goals.utc_offset_hours,
+            ..DailyGoals::default()
+        };
+    } else {
+        goals.minutes = minutes.or(goals.minutes);
+        goals.tests = tests.or(goals.tests);
+        goals.wpm = wpm.or(goals.wpm);
+    }
+    if clear || minutes.is_some() || tests.is_some() || wpm.is_some() || utc_offset.is_some() {
+        goals.save(&path)?;
+    }
+
+    if goals.is_empty() {
+        println!("No daily goals set, e.g. `grumpytype goals --minutes 10 --tests 5 --wpm 60`");
+        return Ok(());
+    }
+
+    let history = RunHistory::load(data_dir(opt).join(RUNS_FILE))?;
+    let utc_offset_hours = goals.utc_offset_hours;
+    let progress = GoalProgress::new(goals, &history.runs, now_timestamp());
+
+    println!(
+        "Daily goals, with days starting at midnight UTC{:+}:",
+        utc_offset_hours
+    );
+    for (line, met) in progress.lines() {
+        println!("  {} {}", if met { "✓" } else { " " }, line);
+    }
+    println!(
+        "{}",
+        if progress.met_today() {
+            "All goals met today"
+        } else {
+            "Goals not met yet today"
+        }
+    );
+    println!(
+        "Streak: {}, longest: {}",
+        plural(progress.current_streak, "day"),
+        plural(progress.longest_streak, "day")
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = SECONDS_PER_DAY as u64;
+    const HOUR: u64 = 60 * 60;
+
+    fn run(timestamp: u64) -> Run {
+        Run {
+            timestamp,
+            duration_secs: 30.0,
+            mode: "normal".to_string(),
+            source: "words".to_string(),
+            practice: false,
+            net_wpm: 60.0,
+            raw_wpm: 60.0,
+            cpm: 300.0,
+            accuracy: Some(1.0),
+            consistency: Some(1.0),
+            correct_words: 30,
+            attempted_words: 30,
+            correct_chars: 150,
+            incorrect_chars: 0,
+            extra_chars: 0,
+            missed_chars: 0,
+            keystrokes: 150,
+            backspaces: 0,
+            word_deletions: 0,
+            idle_secs: 0.0,
+        }
+    }
+
+    /// One run at noon UTC on each of the given days.
+    fn runs_on(days: &[u64]) -> Vec<Run> {
+        days.iter().map(|day| run(day * DAY + 12 * HOUR)).collect()
+    }
+
+    fn one_test_a_day() -> DailyGoals {
+        DailyGoals {
+            tests: Some(1),
+            ..DailyGoals::default()
+        }
+    }
+
+    #[test]
+    fn today_not_yet_met_keeps_the_streak() {
+        let runs = runs_on(&[8, 9, 10]);
+        let progress = GoalProgress::new(one_test_a_day(), &runs, 11 * DAY + HOUR);
+        assert!(!progress.met_today());
+        assert_eq!(progress.current_streak, 3);
+
+        let runs = runs_on(&[8, 9, 10, 11]);
+        let progress = GoalProgress::new(one_test_a_day(), &runs, 11 * DAY + 13 * HOUR);
+        assert!(progress.met_today());
+        assert_eq!(progress.current_streak, 4);
+    }
+
+    #[test]
+    fn missed_day_breaks_the_streak() {
+        let runs = runs_on(&[8, 9, 10]);
+        let progress = GoalProgress::new(one_test_a_day(), &runs, 12 * DAY + HOUR);
+        assert_eq!(progress.current_streak, 0);
+        assert_eq!(progress.longest_streak, 3);
+    }
+
+    #[test]
+    fn longest_streak_is_found_anywhere_in_the_history() {
+        let runs = runs_on(&[1, 2, 3, 4, 6, 8, 9]);
+        let progress = GoalProgress::new(one_test_a_day(), &runs, 9 * DAY + 18 * HOUR);
+        assert_eq!(progress.current_streak, 2);
+        assert_eq!(progress.longest_streak, 4);
+    }
+
+    #[test]
+    fn no_runs_means_no_streaks() {
+        let progress = GoalProgress::new(one_test_a_day(), &[], 9 * DAY);
+        assert!(!progress.met_today());
+        assert_eq!(progress.current_streak, 0);
+        assert_eq!(progress.longest_streak, 0);
+    }
+
+    #[test]
+    fn days_start_at_midnight_utc_by_default() {
+        let runs = vec![run(DAY - 1)];
+        assert!(!GoalProgress::new(one_test_a_day(), &runs, DAY).met_today());
+        assert!(GoalProgress::new(one_test_a_day(), &runs, DAY - 1).met_today());
+
+        let runs = vec![run(DAY)];
+        assert!(GoalProgress::new(one_test_a_day(), &runs, 2 * DAY - 1).met_today());
+        assert!(!GoalProgress::new(one_test_a_day(), &runs, 2 * DAY).met_today());
+    }
+
+    #[test]
+    fn days_start_at_the_local_midnight_with_an_offset() {
+        let ahead = DailyGoals {
+            utc_offset_hours: 2.0,
+            ..one_test_a_day()
+        };
+        // 22:00 UTC is already the next day two hours ahead of UTC
+        let runs = vec![run(DAY - 2 * HOUR)];
+        assert!(GoalProgress::new(ahead.clone(), &runs, DAY).met_today());
+        assert!(!GoalProgress::new(ahead, &runs, DAY - 2 * HOUR - 1).met_today());
+
+        let behind = DailyGoals {
+            utc_offset_hours: -5.5,
+            ..one_test_a_day()
+        };
+        // 05:29 UTC is still the previous day five and a half hours behind UTC
+        let runs = vec![run(DAY + 5 * HOUR + 29 * 60)];
+        assert!(GoalProgress::new(behind.clone(), &runs, DAY - HOUR).met_today());
+        assert!(!GoalProgress::new(behind, &runs, DAY + 5 * HOUR + 30 * 60).met_today());
+    }
+
+    #[test]
+    fn every_goal_has_to_be_met() {
+        let goals = DailyGoals {
+            tests: Some(2),
+            wpm: Some(50.0),
+            ..DailyGoals::default()
+        };
+        let runs = runs_on(&[3]);
+        assert!(!GoalProgress::new(goals.clone(), &runs, 3 * DAY + 18 * HOUR).met_today());
+
+        let runs = runs_on(&[3, 3]);
+        assert!(GoalProgress::new(goals, &runs, 3 * DAY + 18 * HOUR).met_today());
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApplicationError;
+use crate::opt::{CliOptions, ExportFormat};
+use crate::stats::Stats;
+use crate::storage::{data_dir, now_timestamp, read_json, write_json, RUNS_FILE};
+
+/// Column names of exported CSV files, in the order `Run::csv_fields` writes them.
+const CSV_HEADER: [&str; 20] = [
+    "timestamp",
+    "duration_secs",
+    "mode",
+    "source",
+    "practice",
+    "net_wpm",
+    "raw_wpm",
+    "cpm",
+    "accuracy",
+    "consistency",
+    "correct_words",
+    "attempted_words",
+    "correct_chars",
+    "incorrect_chars",
+    "extra_chars",
+    "missed_chars",
+    "keystrokes",
+    "backspaces",
+    "word_deletions",
+    "idle_secs",
+];
+
+/// The summary of a finished test that is kept in the run history.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Run {
+    /// Seconds since the Unix epoch when the test ended.
+    pub timestamp: u64,
+    pub duration_secs: f64,
+    pub mode: String,
+    /// Where the text came from, e.g. the dictionaries or the text file.
+    pub source: String,
+    /// Whether the test practiced the mistakes of the test before it.
+    pub practice: bool,
+    pub net_wpm: f64,
+    pub raw_wpm: f64,
+    pub cpm: f64,
+    pub accuracy: Option<f64>,
+    pub consistency: Option<f64>,
+    pub correct_words: usize,
+    pub attempted_words: usize,
+    pub correct_chars: usize,
+    pub incorrect_chars: usize,
+    pub extra_chars: usize,
+    pub missed_chars: usize,
+    pub keystrokes: usize,
+    pub backspaces: usize,
+    pub word_deletions: usize,
+    pub idle_secs: f64,
+}
+
+impl Run {
+    pub fn new(stats: &Stats, opt: &CliOptions, practice: bool) -> Self {
+        Self {
+            timestamp: now_timestamp(),
+            duration_secs: stats.duration.as_secs_f64(),
+            mode: opt.input_mode.to_string(),
+            source: opt.source_description(),
+            practice,
+            net_wpm: stats.net_wpm,
+            raw_wpm: stats.raw_wpm,
+            cpm: stats.cpm,
+            accuracy: stats.accuracy,
+            consistency: stats.consistency,
+            correct_words: stats.correct_word_count,
+            attempted_words: stats.attempted_word_count,
+            correct_chars: stats.chars.correct,
+            incorrect_chars: stats.chars.incorrect,
+            extra_chars: stats.chars.extra,
+            missed_chars: stats.chars.missed,
+            keystrokes: stats.typed_char_count,
+            backspaces: stats.backspace_count,
+            word_deletions: stats.word_deletion_count,
+            idle_secs: stats.idle_time.as_secs_f64(),
+        }
+    }
+
+    fn csv_fields(&self) -> Vec<String> {
+        let optional = |value: Option<f64>| value.map_or(String::new(), |value| value.to_string());
+        vec![
+            self.timestamp.to_string(),
+            self.duration_secs.to_string(),
+            self.mode.clone(),
+            self.source.clone(),
+            self.practice.to_string(),
+            self.net_wpm.to_string(),
+            self.raw_wpm.to_string(),
+            self.cpm.to_string(),
+            optional(self.accuracy),
+            optional(self.consistency),
+            self.correct_words.to_string(),
+            self.attempted_words.to_string(),
+            self.correct_chars.to_string(),
+            self.incorrect_chars.to_string(),
+            self.extra_chars.to_string(),
+            self.missed_chars.to_string(),
+            self.keystrokes.to_string(),
+            self.backspaces.to_string(),
+            self.word_deletions.to_string(),
+            self.idle_secs.to_string(),
+        ]
+    }
+}
+
+/// A finished test as printed with `--json`: the stored summary along with the full statistics.
+#[derive(Serialize)]
+pub struct RunReport<'a> {
+    #[serde(flatten)]
+    pub run: &'a Run,
+    pub stats: &'a Stats,
+}
+
+/// Every finished test, kept across sessions.
+pub struct RunHistory {
+    path: PathBuf,
+    pub runs: Vec<Run>,
+}
+
+impl RunHistory {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApplicationError> {
+        let path = path.as_ref().to_path_buf();
+        let runs = read_json(&path)?.unwrap_or_default();
+        Ok(Self { path, runs })
+    }
+
+    pub fn save(&self) -> Result<(), ApplicationError> {
+        write_json(&self.path, &self.runs)
+    }
+
+    pub fn record(&mut self, run: Run) {
+        self.runs.push(run);
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_csv(runs: &[Run]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push('\n');
+    for run in runs {
+        let fields: Vec<String> = run.csv_fields().iter().map(|f| csv_field(f)).collect();
+        // Writing to a String can't fail
+        let _ = writeln!(csv, "{}", fields.join(","));
+    }
+    csv
+}
+
+/// Writes every stored run to a file, or to standard output without one.
+pub fn run_export_command(
+    format: ExportFormat,
+    output: Option<&Path>,
+    opt: &CliOptions,
+) -> Result<(), ApplicationError> {
+    let history = RunHistory::load(data_dir(opt).join(RUNS_FILE))?;
+
+    let contents = match format {
+        ExportFormat::Csv => to_csv(&history.runs),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&history.runs).map_err(ApplicationError::JsonSerialize)?
+                + "\n"
+        }
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, contents).map_err(|e| ApplicationError::ExportWrite(path.into(), e))
+        }
+        None => {
+            print!("{}", contents);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str) -> Run {
+        Run {
+            timestamp: 1_600_000_000,
+            duration_secs: 15.0,
+            mode: "normal".to_string(),
+            source: source.to_string(),
+            practice: false,
+            net_wpm: 60.5,
+            raw_wpm: 62.0,
+            cpm: 310.0,
+            accuracy: Some(0.98),
+            consistency: None,
+            correct_words: 15,
+            attempted_words: 16,
+            correct_chars: 75,
+            incorrect_chars: 2,
+            extra_chars: 0,
+            missed_chars: 1,
+            keystrokes: 80,
+            backspaces: 3,
+            word_deletions: 0,
+            idle_secs: 0.0,
+        }
+    }
+
+    #[test]
+    fn plain_fields_are_not_quoted() {
+        assert_eq!(csv_field("words"), "words");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn fields_with_separators_are_quoted() {
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_run() {
+        let csv = to_csv(&[run("words.txt ×2, code.txt"), run("notes.txt")]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "1600000000,15,normal,\"words.txt ×2, code.txt\",false,60.5,62,310,0.98,,15,16,75,2,0,1,80,3,0,0"
+        );
+        assert!(lines[2].starts_with("1600000000,15,normal,notes.txt,false,"));
+        assert!(csv.ends_with('\n'));
+    }
+
+    #[test]
+    fn csv_of_no_runs_is_only_the_header() {
+        assert_eq!(to_csv(&[]), CSV_HEADER.join(",") + "\n");
+    }
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::io::{self, Read};
 use std::sync::mpsc::Sender;
 use std::time::Instant;
 
@@ -6,51 +6,125 @@ use termion::event::Key;
 use termion::input::TermRead;
 
 use crate::error::ApplicationError;
+use crate::opt::InputMode;
 use crate::state::State;
 
-pub fn input_handling(input_sender: Sender<Key>) -> Result<(), ApplicationError> {
-    let keys = io::stdin().keys();
-    for k in keys {
+/// Sends keys typed on the terminal. When standard input was used for the text to type, keys are
+/// read from the terminal device directly instead.
+pub fn input_handling(input_sender: Sender<Key>, use_tty: bool) -> Result<(), ApplicationError> {
+    let source: Box<dyn Read> = if use_tty {
+        Box::new(termion::get_tty().map_err(ApplicationError::TtyOpen)?)
+    } else {
+        Box::new(io::stdin())
+    };
+    for k in source.keys() {
         let k = k.map_err(ApplicationError::InputKey)?;
         input_sender.send(k).map_err(ApplicationError::InputSend)?;
     }
     Ok(())
 }
 
-fn handle_space(state: &mut State) {
-    state.counters.attempted_word_count += 1;
+/// Submits the current word when `separator` is what should follow it: a newline for the last word
+/// on a line and a space everywhere else.
+fn handle_separator(state: &mut State, separator: char) {
+    let expected_separator = if state.ends_line(state.typed_words.len()) {
+        '\n'
+    } else {
+        ' '
+    };
+    if separator != expected_separator {
+        return;
+    }
 
-    let typed_word = &state.current_word;
+    let is_correct = match state.current_expected_word() {
+        Some(expected_word) => &state.current_word == expected_word,
+        None => return,
+    };
 
-    if typed_word == &state.all_words[state.typed_words.len()] {
-        state.counters.correctly_typed_word_count += 1;
+    match state.input_mode {
+        InputMode::StopOnLetter | InputMode::StopOnWord if !is_correct => return,
+        _ => {}
     }
 
-    state.typed_words.push(typed_word.clone());
-    state.current_word = "".into();
+    state.counters.typed_char_count += 1;
+    state.record_keystroke(separator, Some(expected_separator));
+
+    let typed_word = std::mem::take(&mut state.current_word);
+    state.typed_words.push(typed_word);
+
+    if state.current_expected_word().is_none() {
+        state.complete = true;
+    }
+}
+
+/// Whether a space is expected inside the current word rather than after it, for words that end in
+/// part of a run of spaces.
+fn expects_space_in_word(state: &State) -> bool {
+    state
+        .current_expected_word()
+        .and_then(|word| word.strip_prefix(state.current_word.as_str()))
+        .is_some_and(|remaining| remaining.starts_with(' '))
 }
 
-fn handle_alpha(state: &mut State, c: char) {
+fn handle_char(state: &mut State, c: char) {
     if state.start_time.is_none() {
         state.start_time = Some(Instant::now());
     }
+
+    let expected_char = state
+        .current_expected_word()
+        .and_then(|word| word.chars().nth(state.current_word.chars().count()));
+    let is_correct = expected_char == Some(c);
+
+    if state.input_mode == InputMode::StopOnLetter && !is_correct {
+        return;
+    }
+
+    state.counters.typed_char_count += 1;
+    state.record_keystroke(c, expected_char);
     state.current_word.push(c);
 }
 
 fn handle_backspace(state: &mut State) {
+    if state.input_mode == InputMode::Confidence {
+        return;
+    }
+
+    state.counters.backspace_count += 1;
+    state.after_correction = true;
+
     if !state.current_word.is_empty() {
         state.current_word.pop();
+    } else if let Some(previous_typed_word) = pop_incorrect_typed_word(state) {
+        state.current_word = previous_typed_word;
+    }
+}
+
+fn handle_delete_word(state: &mut State) {
+    if state.input_mode == InputMode::Confidence {
+        return;
+    }
+
+    state.counters.word_deletion_count += 1;
+    state.after_correction = true;
+
+    if !state.current_word.is_empty() {
+        state.current_word.clear();
     } else {
-        if state.typed_words.is_empty() {
-            return;
-        }
+        // Back into the previous word, discarding everything typed for it
+        pop_incorrect_typed_word(state);
+    }
+}
 
-        let num_typed_words = state.typed_words.len();
-        if state.all_words[num_typed_words - 1] != state.typed_words[num_typed_words - 1] {
-            let previous_typed_word = state.typed_words.pop().unwrap();
-            state.current_word = previous_typed_word;
-        }
+/// Removes the last typed word if it does not match the expected word. Correctly typed words are
+/// locked in and can't be returned to.
+fn pop_incorrect_typed_word(state: &mut State) -> Option<String> {
+    let previous_word_index = state.typed_words.len().checked_sub(1)?;
+    if state.expected_word(previous_word_index) == state.typed_words.last() {
+        return None;
     }
+
+    state.typed_words.pop()
 }
 
 pub fn handle_key(state: &mut State, k: Key) {
@@ -61,14 +135,36 @@ pub fn handle_key(state: &mut State, k: Key) {
         Key::Ctrl('r') => {
             state.retry = true;
         }
+        Key::Ctrl('t') => {
+            state.restart_same_text = true;
+        }
+        Key::Esc => {
+            state.toggle_pause();
+        }
+        _ if state.is_paused() => {}
+        _ => {
+            handle_typing_key(state, k);
+            state.record_activity();
+        }
+    }
+}
+
+fn handle_typing_key(state: &mut State, k: Key) {
+    match k {
         Key::Backspace => {
             handle_backspace(state);
         }
+        // Ctrl-Backspace is sent as Ctrl-H by most terminals
+        Key::Ctrl('w') | Key::Ctrl('h') | Key::Alt('\x7f') => {
+            handle_delete_word(state);
+        }
         Key::Char(c) => {
-            if c == ' ' {
-                handle_space(state);
-            } else if c.is_ascii_alphabetic() {
-                handle_alpha(state, c);
+            if c == ' ' && expects_space_in_word(state) {
+                handle_char(state, c);
+            } else if c == ' ' || c == '\n' {
+                handle_separator(state, c);
+            } else if c == '\t' || !c.is_control() {
+                handle_char(state, c);
             }
         }
         _ => {}
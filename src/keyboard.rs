@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use termion::event::Key;
+
+use crate::error::ApplicationError;
+
+/// The keys of a US QWERTY keyboard, which incoming keys are assumed to be typed on. Every layout
+/// has rows of the same lengths, with each character on the same physical key as the QWERTY
+/// character in the same position.
+const QWERTY_ROWS: [&str; 4] = [
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+];
+const QWERTY_SHIFTED_ROWS: [&str; 4] = [
+    "~!@#$%^&*()_+",
+    "QWERTYUIOP{}|",
+    "ASDFGHJKL:\"",
+    "ZXCVBNM<>?",
+];
+
+/// The finger that presses each key, by position, for a standard touch typing technique. Digits
+/// are indexes into `Finger::ALL`, from 0 for the left pinky to 8 for the right pinky.
+const FINGER_ROWS: [&str; 4] = [
+    "0012335567888",
+    "0123355678888",
+    "01233556788",
+    "0123355678",
+];
+
+const DVORAK_ROWS: [&str; 4] = [
+    "`1234567890[]",
+    "',.pyfgcrl/=\\",
+    "aoeuidhtns-",
+    ";qjkxbmwvz",
+];
+const COLEMAK_ROWS: [&str; 4] = [
+    "`1234567890-=",
+    "qwfpgjluy;[]\\",
+    "arstdhneio'",
+    "zxcvbkm,./",
+];
+const WORKMAN_ROWS: [&str; 4] = [
+    "`1234567890-=",
+    "qdrwbjfup;[]\\",
+    "ashtgyneoi'",
+    "zxmcvkl,./",
+];
+
+/// Characters paired with the one typed together with shift, for layouts that don't list their
+/// shifted characters.
+const SHIFT_PAIRS: [(char, char); 21] = [
+    ('`', '~'),
+    ('1', '!'),
+    ('2', '@'),
+    ('3', '#'),
+    ('4', '$'),
+    ('5', '%'),
+    ('6', '^'),
+    ('7', '&'),
+    ('8', '*'),
+    ('9', '('),
+    ('0', ')'),
+    ('-', '_'),
+    ('=', '+'),
+    ('[', '{'),
+    (']', '}'),
+    ('\\', '|'),
+    (';', ':'),
+    ('\'', '"'),
+    (',', '<'),
+    ('.', '>'),
+    ('/', '?'),
+];
+
+fn shifted_char(c: char) -> char {
+    if let Some((_, shifted)) = SHIFT_PAIRS.iter().find(|(unshifted, _)| *unshifted == c) {
+        return *shifted;
+    }
+    c.to_uppercase().next().unwrap_or(c)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    Thumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    pub const ALL: [Hand; 2] = [Hand::Left, Hand::Right];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Hand::Left => "left hand",
+            Hand::Right => "right hand",
+        }
+    }
+}
+
+impl Finger {
+    pub const ALL: [Finger; 9] = [
+        Finger::LeftPinky,
+        Finger::LeftRing,
+        Finger::LeftMiddle,
+        Finger::LeftIndex,
+        Finger::Thumb,
+        Finger::RightIndex,
+        Finger::RightMiddle,
+        Finger::RightRing,
+        Finger::RightPinky,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "left pinky",
+            Finger::LeftRing => "left ring finger",
+            Finger::LeftMiddle => "left middle finger",
+            Finger::LeftIndex => "left index finger",
+            Finger::Thumb => "thumb",
+            Finger::RightIndex => "right index finger",
+            Finger::RightMiddle => "right middle finger",
+            Finger::RightRing => "right ring finger",
+            Finger::RightPinky => "right pinky",
+        }
+    }
+
+    /// A name short enough for a table column.
+    pub fn short_name(self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "L pinky",
+            Finger::LeftRing => "L ring",
+            Finger::LeftMiddle => "L middle",
+            Finger::LeftIndex => "L index",
+            Finger::Thumb => "thumb",
+            Finger::RightIndex => "R index",
+            Finger::RightMiddle => "R middle",
+            Finger::RightRing => "R ring",
+            Finger::RightPinky => "R pinky",
+        }
+    }
+
+    /// `None` for the thumb, which either hand may use.
+    pub fn hand(self) -> Option<Hand> {
+        match self {
+            Finger::LeftPinky | Finger::LeftRing | Finger::LeftMiddle | Finger::LeftIndex => {
+                Some(Hand::Left)
+            }
+            Finger::Thumb => None,
+            Finger::RightIndex | Finger::RightMiddle | Finger::RightRing | Finger::RightPinky => {
+                Some(Hand::Right)
+            }
+        }
+    }
+
+    fn from_digit(digit: char) -> Option<Finger> {
+        let index = digit.to_digit(10)? as usize;
+        Finger::ALL.get(index).copied()
+    }
+}
+
+/// Where a character is typed on the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyPosition {
+    /// A key in the main rows, along with whether shift has to be held.
+    Key {
+        row: usize,
+        column: usize,
+        shifted: bool,
+    },
+    Space,
+    Enter,
+    Tab,
+}
+
+impl KeyPosition {
+    /// The keyboard row, counting from 0 for the number row. The space bar is the row below the
+    /// bottom letter row, and enter and tab are on the middle rows.
+    pub fn row(self) -> usize {
+        match self {
+            KeyPosition::Key { row, .. } => row,
+            KeyPosition::Space => QWERTY_ROWS.len(),
+            KeyPosition::Enter => 2,
+            KeyPosition::Tab => 1,
+        }
+    }
+
+    /// Whether both positions are the same physical key, ignoring shift.
+    pub fn same_key(self, other: KeyPosition) -> bool {
+        match (self, other) {
+            (
+                KeyPosition::Key { row, column, .. },
+                KeyPosition::Key {
+                    row: other_row,
+                    column: other_column,
+                    ..
+                },
+            ) => row == other_row && column == other_column,
+            _ => self == other,
+        }
+    }
+}
+
+/// The contents of a custom layout file.
+#[derive(Deserialize)]
+struct LayoutFile {
+    name: Option<String>,
+    rows: Vec<String>,
+    shifted_rows: Option<Vec<String>>,
+    /// Rows of digits giving the finger for each key, as in `FINGER_ROWS`.
+    fingers: Option<Vec<String>>,
+}
+
+pub struct KeyboardLayout {
+    pub name: String,
+    pub rows: Vec<Vec<char>>,
+    shifted_rows: Vec<Vec<char>>,
+    finger_rows: Vec<Vec<Finger>>,
+    /// Maps characters typed on the QWERTY keyboard to the ones on the same keys in this layout.
+    remap: HashMap<char, char>,
+}
+
+impl KeyboardLayout {
+    /// Loads one of the built-in layouts by name, or else a custom layout from a TOML file.
+    pub fn load(name: &str) -> Result<Self, ApplicationError> {
+        let rows = match name.to_lowercase().as_str() {
+            "qwerty" => QWERTY_ROWS,
+            "dvorak" => DVORAK_ROWS,
+            "colemak" => COLEMAK_ROWS,
+            "workman" => WORKMAN_ROWS,
+            _ => return Self::from_file(Path::new(name)),
+        };
+        let rows = rows.iter().map(|row| row.to_string()).collect();
+        Self::new(name.to_lowercase(), rows, None, None)
+            .map_err(|message| ApplicationError::LayoutInvalid(name.into(), message))
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ApplicationError> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| ApplicationError::LayoutLoad(path.into(), e))?;
+        let file: LayoutFile =
+            toml::from_str(&contents).map_err(|e| ApplicationError::LayoutParse(path.into(), e))?;
+
+        let name = file.name.unwrap_or_else(|| {
+            path.file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into()
+        });
+        Self::new(name, file.rows, file.shifted_rows, file.fingers)
+            .map_err(|message| ApplicationError::LayoutInvalid(path.into(), message))
+    }
+
+    fn new(
+        name: String,
+        rows: Vec<String>,
+        shifted_rows: Option<Vec<String>>,
+        fingers: Option<Vec<String>>,
+    ) -> Result<Self, String> {
+        let rows: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+        let shifted_rows: Vec<Vec<char>> = match shifted_rows {
+            Some(shifted_rows) => shifted_rows
+                .iter()
+                .map(|row| row.chars().collect())
+                .collect(),
+            None => rows
+                .iter()
+                .map(|row| row.iter().copied().map(shifted_char).collect())
+                .collect(),
+        };
+
+        let fingers: Vec<Vec<char>> = match fingers {
+            Some(fingers) => fingers.iter().map(|row| row.chars().collect()).collect(),
+            None => FINGER_ROWS
+                .iter()
+                .map(|row| row.chars().collect())
+                .collect(),
+        };
+
+        for (name, rows) in [
+            ("rows", &rows),
+            ("shifted_rows", &shifted_rows),
+            ("fingers", &fingers),
+        ]
+        .iter()
+        {
+            if rows.len() != QWERTY_ROWS.len() {
+                return Err(format!(
+                    "{} should have {} rows, found {}",
+                    name,
+                    QWERTY_ROWS.len(),
+                    rows.len()
+                ));
+            }
+            for (index, (row, qwerty_row)) in rows.iter().zip(QWERTY_ROWS.iter()).enumerate() {
+                let expected_len = qwerty_row.chars().count();
+                if row.len() != expected_len {
+                    return Err(format!(
+                        "row {} of {} should have {} keys, found {}",
+                        index + 1,
+                        name,
+                        expected_len,
+                        row.len()
+                    ));
+                }
+            }
+        }
+
+        let finger_rows = fingers
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&digit| {
+                        Finger::from_digit(digit).ok_or_else(|| {
+                            format!("invalid finger '{}', expected a digit from 0 to 8", digit)
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut remap = HashMap::new();
+        for (qwerty_rows, layout_rows) in
+            [(QWERTY_ROWS, &rows), (QWERTY_SHIFTED_ROWS, &shifted_rows)].iter()
+        {
+            for (qwerty_row, layout_row) in qwerty_rows.iter().zip(layout_rows.iter()) {
+                for (qwerty_char, layout_char) in qwerty_row.chars().zip(layout_row.iter()) {
+                    remap.insert(qwerty_char, *layout_char);
+                }
+            }
+        }
+
+        Ok(Self {
+            name,
+            rows,
+            shifted_rows,
+            finger_rows,
+            remap,
+        })
+    }
+
+    /// The finger that presses the key at `position`.
+    pub fn finger(&self, position: KeyPosition) -> Finger {
+        match position {
+            KeyPosition::Key { row, column, .. } => self.finger_rows[row][column],
+            KeyPosition::Space => Finger::Thumb,
+            KeyPosition::Enter => Finger::RightPinky,
+            KeyPosition::Tab => Finger::LeftPinky,
+        }
+    }
+
+    /// Where `c` is typed in this layout, if it can be typed at all.
+    pub fn position(&self, c: char) -> Option<KeyPosition> {
+        match c {
+            ' ' => return Some(KeyPosition::Space),
+            '\n' => return Some(KeyPosition::Enter),
+            '\t' => return Some(KeyPosition::Tab),
+            _ => {}
+        }
+
+        for (shifted, rows) in [(false, &self.rows), (true, &self.shifted_rows)].iter() {
+            for (row, keys) in rows.iter().enumerate() {
+                if let Some(column) = keys.iter().position(|&key| key == c) {
+                    return Some(KeyPosition::Key {
+                        row,
+                        column,
+                        shifted: *shifted,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// The key that would have been typed if the operating system used this layout.
+    pub fn remap_key(&self, key: Key) -> Key {
+        match key {
+            Key::Char(c) => Key::Char(self.remap.get(&c).copied().unwrap_or(c)),
+            _ => key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[test]
+    fn built_in_rows_are_valid() {
+        for name in &["qwerty", "dvorak", "colemak", "workman"] {
+            assert!(KeyboardLayout::load(name).is_ok(), "{} should load", name);
+        }
+    }
+
+    #[test]
+    fn remaps_qwerty_keys() {
+        let layout = KeyboardLayout::load("colemak").unwrap();
+        assert_eq!(layout.remap_key(Key::Char('e')), Key::Char('f'));
+        assert_eq!(layout.remap_key(Key::Char('E')), Key::Char('F'));
+        assert_eq!(layout.remap_key(Key::Char(' ')), Key::Char(' '));
+        assert_eq!(layout.remap_key(Key::Backspace), Key::Backspace);
+    }
+
+    #[test]
+    fn wrong_row_count() {
+        let error = KeyboardLayout::new("test".into(), rows(&QWERTY_ROWS[..3]), None, None).err();
+        assert_eq!(error.as_deref(), Some("rows should have 4 rows, found 3"));
+    }
+
+    #[test]
+    fn wrong_key_count() {
+        let mut short_rows = rows(&QWERTY_ROWS);
+        short_rows[2].pop();
+        let error = KeyboardLayout::new("test".into(), short_rows, None, None).err();
+        assert_eq!(
+            error.as_deref(),
+            Some("row 3 of rows should have 11 keys, found 10")
+        );
+    }
+
+    #[test]
+    fn wrong_shifted_rows() {
+        let mut shifted_rows = rows(&QWERTY_SHIFTED_ROWS);
+        shifted_rows[0].push('!');
+        let error =
+            KeyboardLayout::new("test".into(), rows(&QWERTY_ROWS), Some(shifted_rows), None).err();
+        assert_eq!(
+            error.as_deref(),
+            Some("row 1 of shifted_rows should have 13 keys, found 14")
+        );
+    }
+
+    #[test]
+    fn custom_fingers() {
+        let mut fingers = rows(&FINGER_ROWS);
+        fingers[3] = "4444444444".to_string();
+        let layout =
+            KeyboardLayout::new("test".into(), rows(&QWERTY_ROWS), None, Some(fingers)).unwrap();
+        let position = layout.position('z').unwrap();
+        assert_eq!(layout.finger(position), Finger::Thumb);
+    }
+
+    #[test]
+    fn wrong_finger_rows() {
+        let error = KeyboardLayout::new(
+            "test".into(),
+            rows(&QWERTY_ROWS),
+            None,
+            Some(rows(&FINGER_ROWS[1..])),
+        )
+        .err();
+        assert_eq!(
+            error.as_deref(),
+            Some("fingers should have 4 rows, found 3")
+        );
+
+        let mut fingers = rows(&FINGER_ROWS);
+        fingers[1].push('8');
+        let error =
+            KeyboardLayout::new("test".into(), rows(&QWERTY_ROWS), None, Some(fingers)).err();
+        assert_eq!(
+            error.as_deref(),
+            Some("row 2 of fingers should have 13 keys, found 14")
+        );
+    }
+
+    #[test]
+    fn invalid_finger_digit() {
+        let mut fingers = rows(&FINGER_ROWS);
+        fingers[0].replace_range(0..1, "9");
+        let error =
+            KeyboardLayout::new("test".into(), rows(&QWERTY_ROWS), None, Some(fingers)).err();
+        assert_eq!(
+            error.as_deref(),
+            Some("invalid finger '9', expected a digit from 0 to 8")
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::keyboard::{KeyPosition, KeyboardLayout};
+use crate::storage::serialize_millis;
+
+/// Timing of one bigram over all the times it was typed.
+#[derive(Clone, Debug, Serialize)]
+pub struct BigramLatency {
+    pub bigram: String,
+    pub count: usize,
+    #[serde(rename = "average_ms", serialize_with = "serialize_millis")]
+    pub average: Duration,
+    #[serde(rename = "median_ms", serialize_with = "serialize_millis")]
+    pub median: Duration,
+}
+
+/// How the two keys of a bigram are pressed relative to each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Alternating,
+    SameHand,
+    SameFinger,
+    SameKey,
+}
+
+impl Transition {
+    pub const ALL: [Transition; 4] = [
+        Transition::Alternating,
+        Transition::SameHand,
+        Transition::SameFinger,
+        Transition::SameKey,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Transition::Alternating => "Alternating hands",
+            Transition::SameHand => "Same hand",
+            Transition::SameFinger => "Same finger",
+            Transition::SameKey => "Same key",
+        }
+    }
+
+    fn between(layout: &KeyboardLayout, first: KeyPosition, second: KeyPosition) -> Option<Self> {
+        let (first_finger, second_finger) = (layout.finger(first), layout.finger(second));
+        if first.same_key(second) {
+            Some(Transition::SameKey)
+        } else if first_finger == second_finger {
+            Some(Transition::SameFinger)
+        } else if first_finger.hand()? == second_finger.hand()? {
+            Some(Transition::SameHand)
+        } else {
+            Some(Transition::Alternating)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TransitionStats {
+    pub transition: Transition,
+    /// Fraction of all bigrams.
+    pub share: f64,
+    #[serde(rename = "average_ms", serialize_with = "serialize_millis")]
+    pub average: Duration,
+}
+
+fn average(durations: &[Duration]) -> Duration {
+    durations.iter().sum::<Duration>() / durations.len().max(1) as u32
+}
+
+/// The middle of sorted durations, or the average of the two middle ones for an even count.
+fn median(sorted: &[Duration]) -> Duration {
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        average(&sorted[middle.saturating_sub(1)..middle + 1])
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Averages and medians of each bigram's timings, slowest on average first.
+pub fn bigram_latencies<I>(samples: I) -> Vec<BigramLatency>
+where
+    I: IntoIterator<Item = (String, Duration)>,
+{
+    let mut by_bigram: HashMap<String, Vec<Duration>> = HashMap::new();
+    for (bigram, latency) in samples {
+        by_bigram.entry(bigram).or_default().push(latency);
+    }
+
+    let mut latencies: Vec<BigramLatency> = by_bigram
+        .into_iter()
+        .map(|(bigram, mut durations)| {
+            durations.sort();
+            BigramLatency {
+                bigram,
+                count: durations.len(),
+                average: average(&durations),
+                median: median(&durations),
+            }
+        })
+        .collect();
+    latencies.sort_by(|a, b| b.average.cmp(&a.average).then(a.bigram.cmp(&b.bigram)));
+    latencies
+}
+
+/// How often each kind of transition between fingers and hands happens and how long it takes.
+/// Bigrams with a character that isn't on the layout are left out.
+pub fn transition_stats(
+    samples: &[(String, Duration)],
+    layout: &KeyboardLayout,
+) -> Vec<TransitionStats> {
+    let mut by_transition: HashMap<Transition, Vec<Duration>> = HashMap::new();
+    for (bigram, latency) in samples {
+        let mut chars = bigram.chars().map(|c| layout.position(c));
+        let transition = match (chars.next().flatten(), chars.next().flatten()) {
+            (Some(first), Some(second)) => Transition::between(layout, first, second),
+            _ => None,
+        };
+        if let Some(transition) = transition {
+            by_transition.entry(transition).or_default().push(*latency);
+        }
+    }
+
+    let total: usize = by_transition.values().map(Vec::len).sum();
+    Transition::ALL
+        .iter()
+        .filter_map(|&transition| {
+            let durations = by_transition.get(&transition)?;
+            Some(TransitionStats {
+                transition,
+                share: durations.len() as f64 / total as f64,
+                average: average(durations),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(samples: &[(&str, u64)]) -> Vec<(String, Duration)> {
+        samples
+            .iter()
+            .map(|&(bigram, ms)| (bigram.to_string(), Duration::from_millis(ms)))
+            .collect()
+    }
+
+    #[test]
+    fn slowest_bigrams_come_first() {
+        let latencies = bigram_latencies(samples(&[
+            ("th", 100),
+            ("he", 300),
+            ("in", 200),
+            ("th", 140),
+            ("an", 200),
+        ]));
+        let order: Vec<&str> = latencies.iter().map(|l| l.bigram.as_str()).collect();
+        // Bigrams with the same average are in alphabetical order
+        assert_eq!(order, vec!["he", "an", "in", "th"]);
+        assert_eq!(latencies[3].count, 2);
+        assert_eq!(latencies[3].average, Duration::from_millis(120));
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        let latencies = bigram_latencies(samples(&[
+            ("th", 300),
+            ("th", 100),
+            ("th", 120),
+            ("he", 100),
+            ("he", 400),
+            ("he", 120),
+            ("he", 140),
+        ]));
+        let median = |bigram: &str| {
+            latencies
+                .iter()
+                .find(|l| l.bigram == bigram)
+                .map(|l| l.median)
+        };
+        assert_eq!(median("th"), Some(Duration::from_millis(120)));
+        assert_eq!(median("he"), Some(Duration::from_millis(130)));
+    }
+
+    #[test]
+    fn transitions_between_keys() {
+        let layout = KeyboardLayout::load("qwerty").unwrap();
+        let stats = transition_stats(
+            &samples(&[
+                ("aj", 100),
+                ("fj", 140),
+                ("as", 150),
+                ("ed", 200),
+                ("ee", 120),
+            ]),
+            &layout,
+        );
+        let found: Vec<(Transition, f64, Duration)> = stats
+            .iter()
+            .map(|s| (s.transition, s.share, s.average))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Transition::Alternating, 0.4, Duration::from_millis(120)),
+                (Transition::SameHand, 0.2, Duration::from_millis(150)),
+                (Transition::SameFinger, 0.2, Duration::from_millis(200)),
+                (Transition::SameKey, 0.2, Duration::from_millis(120)),
+            ]
+        );
+    }
+
+    #[test]
+    fn thumb_and_unknown_keys_are_left_out() {
+        let layout = KeyboardLayout::load("qwerty").unwrap();
+        // The thumb isn't on either hand, and é isn't on the layout
+        let stats = transition_stats(&samples(&[("a ", 100), (" j", 100), ("éa", 100)]), &layout);
+        assert!(stats.is_empty());
+
+        let stats = transition_stats(&samples(&[("  ", 100), ("a ", 100)]), &layout);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].transition, Transition::SameKey);
+        assert_eq!(stats[0].share, 1.0);
+    }
+}
@@ -1,58 +1,307 @@
+mod code;
 mod dictionary;
+mod drill;
 mod error;
+mod fingers;
+mod goals;
+mod history;
 mod input;
+mod keyboard;
+mod latency;
+mod ngram;
 mod opt;
+mod pseudo;
 mod render;
+mod report;
+mod source;
 mod state;
 mod stats;
+mod storage;
+mod summary;
+mod terminal;
+mod trouble;
+mod wordlist;
 
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process;
 use std::sync::mpsc::channel;
 use std::thread;
 
 use structopt::StructOpt;
 
+use code::CodeSnippets;
 use dictionary::Dictionary;
+use drill::LetterDrill;
 use error::ApplicationError;
+use fingers::FingerHistory;
+use goals::{load_goal_progress, run_goals_command};
+use history::{run_export_command, Run, RunHistory, RunReport};
 use input::input_handling;
-use opt::CliOptions;
+use keyboard::KeyboardLayout;
+use ngram::{NgramSelection, NgramTimings};
+use opt::{CliOptions, Command, DictionarySource};
 use render::{render_stats, render_typing_test};
+use report::run_stats_command;
+use source::TextSource;
 use state::State;
+use stats::Stats;
+use summary::{goal_met, print_summary};
+use terminal::TerminalOutput;
+use trouble::TroubleWords;
+use wordlist::run_wordlist_command;
 
-fn main() -> Result<(), ApplicationError> {
-    let opt = CliOptions::from_args();
+/// How many of the slowest n-grams are drilled with `--ngrams slowest`.
+const SLOWEST_NGRAM_COUNT: usize = 6;
+
+/// Exit status when the last test missed the goal set with `--goal-wpm` or `--goal-accuracy`.
+const GOAL_MISSED_EXIT_CODE: i32 = 2;
+
+/// Reads the text to type from a file, or from standard input if the path is `-`.
+fn read_text(path: &Path) -> Result<String, ApplicationError> {
+    if path.as_os_str() == "-" {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(ApplicationError::TextLoad)?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).map_err(ApplicationError::TextLoad)
+    }
+}
+
+/// Runs the command or typing tests, returning whether the goal of the last test was met.
+fn run(opt: CliOptions) -> Result<bool, ApplicationError> {
+    let result = match &opt.command {
+        Some(Command::Wordlist(command)) => run_wordlist_command(command, &opt),
+        Some(Command::Stats { limit }) => run_stats_command(*limit, &opt),
+        Some(Command::Export { format, output }) => {
+            run_export_command(*format, output.as_deref(), &opt)
+        }
+        Some(Command::Goals {
+            minutes,
+            tests,
+            wpm,
+            clear,
+            utc_offset,
+        }) => run_goals_command(*minutes, *tests, *wpm, *clear, *utc_offset, &opt),
+        None => return run_tests(&opt),
+    };
+    result.map(|()| true)
+}
+
+fn run_tests(opt: &CliOptions) -> Result<bool, ApplicationError> {
+    let dictionaries: Vec<DictionarySource> = opt
+        .dictionaries
+        .iter()
+        .map(|source| DictionarySource {
+            path: storage::resolve_word_list(opt, &source.path),
+            ..source.clone()
+        })
+        .collect();
+
+    // Per-list bounds are checked when the lists load, but drills use the global ones directly
+    if opt.min_word_len > opt.max_word_len {
+        return Err(ApplicationError::WordLengthBounds(
+            opt.min_word_len,
+            opt.max_word_len,
+        ));
+    }
+
+    let mut text_source = match (opt.text_path(), &opt.code_dir) {
+        (Some(text_path), _) => {
+            TextSource::Words(Box::new(Dictionary::from_text(&read_text(text_path)?)?))
+        }
+        (None, Some(code_dir)) => {
+            TextSource::Code(CodeSnippets::from_dir(code_dir, opt.snippet_lines)?)
+        }
+        (None, None) => TextSource::Words(Box::new(Dictionary::from_sources(
+            &dictionaries,
+            opt.min_word_len,
+            opt.max_word_len,
+        )?)),
+    };
+
+    text_source.set_pseudo_word_ratio(opt.pseudo_words / 100.0);
+
+    // Used for finger statistics even when the keyboard isn't shown
+    let keyboard_layout = KeyboardLayout::load(opt.layout_name())?;
+    let show_keyboard = opt.layout.is_some() || opt.keyboard;
+
+    let mut letter_drill = opt.letters.as_ref().map(|letters| {
+        LetterDrill::new(
+            letters,
+            &opt.unlock_order,
+            opt.unlock_accuracy,
+            opt.unlock_wpm,
+        )
+    });
+    if let Some(drill) = &letter_drill {
+        if drill.letters().is_empty() {
+            return Err(ApplicationError::DrillLettersEmpty);
+        }
+        text_source.set_allowed_letters(drill.letters());
+    }
+
+    let mut trouble_words =
+        TroubleWords::load(storage::data_dir(opt).join(storage::TROUBLE_WORDS_FILE))?;
+    if opt.practice_missed {
+        text_source.set_practice_words(trouble_words.weighted_words());
+    }
+
+    let mut ngram_timings =
+        NgramTimings::load(storage::data_dir(opt).join(storage::NGRAM_TIMINGS_FILE))?;
+    if let Some(selection) = &opt.ngrams {
+        text_source.set_ngrams(Some(ngram_timings.select(selection, SLOWEST_NGRAM_COUNT)));
+    }
+
+    let mut finger_history =
+        FingerHistory::load(storage::data_dir(opt).join(storage::FINGER_USAGE_FILE))?;
 
-    let mut dictionary =
-        Dictionary::from_file(opt.dictionary_path, opt.min_word_len, opt.max_word_len)?;
+    let mut run_history = RunHistory::load(storage::data_dir(opt).join(storage::RUNS_FILE))?;
+    // The last finished test, printed on exit with --json
+    let mut last_result = None;
 
     let (sender, receiver) = channel();
 
-    thread::spawn(|| {
-        input_handling(sender).unwrap();
+    let use_tty = opt.text_from_stdin();
+    thread::spawn(move || {
+        input_handling(sender, use_tty).unwrap();
     });
 
+    // Set while practicing the words mistyped in an earlier test
+    let mut practice_source = None;
+    let mut repeat_text = false;
+
+    // The interface is drawn on the terminal even when standard output is redirected, which keeps
+    // standard output for the --json output
+    let terminal_output = TerminalOutput::redirect()?;
+
     loop {
-        let mut state = State::default();
+        let source = practice_source.as_mut().unwrap_or(&mut text_source);
+        source.start_test(repeat_text)?;
+
+        let mut state = State {
+            input_mode: opt.input_mode,
+            ..State::default()
+        };
         render_typing_test(
             &mut state,
-            &mut dictionary,
+            source,
+            Some(&keyboard_layout).filter(|_| show_keyboard),
             &receiver,
-            opt.display_lines,
-            opt.time_limit,
+            opt,
         )?;
 
         if state.quit {
             break;
         }
 
-        if state.retry {
+        repeat_text = state.restart_same_text;
+        if state.retry || state.restart_same_text {
+            if state.retry {
+                practice_source = None;
+            }
             continue;
         }
 
-        render_stats(&mut state, &receiver, opt.time_limit).unwrap();
+        // Code and given texts aren't made of dictionary words, and their punctuation would end up
+        // in later practice
+        if text_source.is_random_words() {
+            trouble_words.record_test(&state);
+            trouble_words.save()?;
+            if opt.practice_missed {
+                text_source.set_practice_words(trouble_words.weighted_words());
+            }
+        }
+
+        // Punctuation in code and the repeated words of mistake practice would skew which n-grams
+        // come out slowest
+        if practice_source.is_none() && text_source.is_random_words() {
+            ngram_timings.record(&state.keystrokes);
+            ngram_timings.save()?;
+            if let Some(NgramSelection::Slowest) = &opt.ngrams {
+                text_source.set_ngrams(Some(
+                    ngram_timings.select(&NgramSelection::Slowest, SLOWEST_NGRAM_COUNT),
+                ));
+            }
+        }
+
+        let stats = Stats::from_state(&state, opt.time_limit, &keyboard_layout);
+        finger_history.record(&keyboard_layout, &stats.finger_usage);
+        finger_history.save()?;
+
+        let run = Run::new(&stats, opt, practice_source.is_some());
+        run_history.record(run.clone());
+        run_history.save()?;
+        let goal_progress = load_goal_progress(opt, &run_history)?;
+        let (_, stats) = last_result.insert((run, stats));
+
+        // Only tests of the drill's own words count towards unlocking letters
+        if let (Some(drill), None) = (&mut letter_drill, &practice_source) {
+            if drill.record_result(stats) {
+                text_source.set_allowed_letters(drill.letters());
+            }
+        }
+
+        render_stats(
+            &mut state,
+            stats,
+            letter_drill.as_ref(),
+            goal_progress.as_ref(),
+            &receiver,
+            opt,
+        )
+        .unwrap();
         if state.quit {
             break;
         }
+
+        repeat_text = state.restart_same_text;
+        if state.practice_mistakes {
+            practice_source = Some(TextSource::from_mistakes(&stats.mistyped_words));
+        } else if !repeat_text {
+            practice_source = None;
+        }
+    }
+    drop(terminal_output);
+
+    // The interface has closed and standard output is back where it was, so this can be saved
+    // to a file
+    match &last_result {
+        Some((run, stats)) if opt.json => {
+            let report = RunReport { run, stats };
+            let json =
+                serde_json::to_string_pretty(&report).map_err(ApplicationError::JsonSerialize)?;
+            println!("{}", json);
+        }
+        Some((_, stats)) => print_summary(stats, opt),
+        None => {}
     }
 
-    Ok(())
+    let goal_set = opt.goal_wpm.is_some() || opt.goal_accuracy.is_some();
+    Ok(match &last_result {
+        Some((_, stats)) => goal_met(stats, opt),
+        None => !goal_set,
+    })
+}
+
+fn main() {
+    let opt = CliOptions::from_args();
+
+    match run(opt) {
+        Ok(true) => {}
+        Ok(false) => process::exit(GOAL_MISSED_EXIT_CODE),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            let mut source = e.source();
+            while let Some(cause) = source {
+                eprintln!("  caused by: {}", cause);
+                source = cause.source();
+            }
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::ApplicationError;
+use crate::state::{Keystroke, IDLE_THRESHOLD};
+use crate::storage::{read_json, write_json};
+
+/// Only the most recent timings of each n-gram are kept, so that they reflect current speed.
+const MAX_SAMPLES: usize = 50;
+
+/// N-grams need at least this many timings to be picked as the slowest.
+const MIN_SAMPLES: usize = 5;
+
+/// Drilled when there are no timings yet to find the slowest n-grams from.
+const COMMON_NGRAMS: [&str; 8] = ["th", "he", "in", "er", "an", "ing", "ion", "the"];
+
+/// Which n-grams to drill.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NgramSelection {
+    /// The slowest n-grams according to earlier tests.
+    Slowest,
+    List(Vec<String>),
+}
+
+impl FromStr for NgramSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "slowest" {
+            return Ok(NgramSelection::Slowest);
+        }
+
+        let ngrams: Vec<String> = s
+            .split(',')
+            .map(str::trim)
+            .filter(|ngram| !ngram.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if ngrams.is_empty() {
+            return Err("expected 'slowest' or a comma separated list of n-grams".to_string());
+        }
+        Ok(NgramSelection::List(ngrams))
+    }
+}
+
+impl fmt::Display for NgramSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NgramSelection::Slowest => f.write_str("slowest"),
+            NgramSelection::List(ngrams) => f.write_str(&ngrams.join(",")),
+        }
+    }
+}
+
+/// The time from the first to the last keystroke of every `n` consecutive correct characters
+/// within a word. Sequences broken up by a correction or an idle gap are left out.
+pub fn ngram_latencies(keystrokes: &[Keystroke], n: usize) -> Vec<(String, Duration)> {
+    if n < 2 {
+        return Vec::new();
+    }
+
+    keystrokes
+        .windows(n)
+        .filter(|window| {
+            window
+                .iter()
+                .all(|keystroke| keystroke.is_correct() && !keystroke.typed.is_whitespace())
+                && window[1..]
+                    .iter()
+                    .all(|keystroke| !keystroke.after_correction)
+                && window
+                    .windows(2)
+                    .all(|pair| pair[1].elapsed - pair[0].elapsed <= IDLE_THRESHOLD)
+        })
+        .map(|window| {
+            let ngram = window
+                .iter()
+                .flat_map(|keystroke| keystroke.typed.to_lowercase())
+                .collect();
+            (ngram, window[n - 1].elapsed - window[0].elapsed)
+        })
+        .collect()
+}
+
+/// Recent timings of bigrams and trigrams, kept across sessions.
+pub struct NgramTimings {
+    path: PathBuf,
+    /// Milliseconds taken by each of the most recent times an n-gram was typed.
+    samples: HashMap<String, Vec<u64>>,
+}
+
+impl NgramTimings {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApplicationError> {
+        let path = path.as_ref().to_path_buf();
+        let samples = read_json(&path)?.unwrap_or_default();
+        Ok(Self { path, samples })
+    }
+
+    pub fn save(&self) -> Result<(), ApplicationError> {
+        write_json(&self.path, &self.samples)
+    }
+
+    pub fn record(&mut self, keystrokes: &[Keystroke]) {
+        for n in 2..=3 {
+            for (ngram, latency) in ngram_latencies(keystrokes, n) {
+                let samples = self.samples.entry(ngram).or_default();
+                samples.push(latency.as_millis() as u64);
+                if samples.len() > MAX_SAMPLES {
+                    samples.remove(0);
+                }
+            }
+        }
+    }
+
+    /// Every stored bigram timing.
+    pub fn bigram_samples(&self) -> Vec<(String, Duration)> {
+        self.samples
+            .iter()
+            .filter(|(ngram, _)| ngram.chars().count() == 2)
+            .flat_map(|(bigram, samples)| {
+                samples
+                    .iter()
+                    .map(move |&millis| (bigram.clone(), Duration::from_millis(millis)))
+            })
+            .collect()
+    }
+
+    fn average_per_char(samples: &[u64], ngram: &str) -> f64 {
+        let transitions = ngram.chars().count().saturating_sub(1).max(1);
+        samples.iter().sum::<u64>() as f64 / samples.len() as f64 / transitions as f64
+    }
+
+    /// The n-grams that take the longest per keystroke, slowest first.
+    pub fn slowest(&self, count: usize) -> Vec<String> {
+        let mut ngrams: Vec<(&String, f64)> = self
+            .samples
+            .iter()
+            .filter(|(_, samples)| samples.len() >= MIN_SAMPLES)
+            .map(|(ngram, samples)| (ngram, Self::average_per_char(samples, ngram)))
+            .collect();
+        ngrams.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        ngrams
+            .into_iter()
+            .take(count)
+            .map(|(ngram, _)| ngram.clone())
+            .collect()
+    }
+
+    /// The n-grams to drill for a selection, falling back to common ones when there aren't
+    /// enough timings yet.
+    pub fn select(&self, selection: &NgramSelection, count: usize) -> Vec<String> {
+        match selection {
+            NgramSelection::List(ngrams) => ngrams.clone(),
+            NgramSelection::Slowest => {
+                let slowest = self.slowest(count);
+                if slowest.is_empty() {
+                    COMMON_NGRAMS
+                        .iter()
+                        .map(|ngram| ngram.to_string())
+                        .collect()
+                } else {
+                    slowest
+                }
+            }
+        }
+    }
+}
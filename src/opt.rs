@@ -1,25 +1,480 @@
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
+use itertools::Itertools;
 use structopt::StructOpt;
 
+use crate::drill::DEFAULT_UNLOCK_ORDER;
+use crate::ngram::NgramSelection;
+
+/// Controls how strictly mistakes are handled while typing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputMode {
+    /// Mistakes are allowed and can be corrected with backspace.
+    #[default]
+    Normal,
+    /// Incorrect characters are rejected until the correct one is typed.
+    StopOnLetter,
+    /// Space does nothing until the current word is typed correctly.
+    StopOnWord,
+    /// Backspace is disabled entirely.
+    Confidence,
+}
+
+impl FromStr for InputMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(InputMode::Normal),
+            "stop-on-letter" => Ok(InputMode::StopOnLetter),
+            "stop-on-word" => Ok(InputMode::StopOnWord),
+            "confidence" => Ok(InputMode::Confidence),
+            _ => Err(format!(
+                "unknown input mode '{}', expected one of: normal, stop-on-letter, stop-on-word, confidence",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for InputMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InputMode::Normal => "normal",
+            InputMode::StopOnLetter => "stop-on-letter",
+            InputMode::StopOnWord => "stop-on-word",
+            InputMode::Confidence => "confidence",
+        };
+        f.write_str(name)
+    }
+}
+
+/// File format of exported runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!(
+                "unknown export format '{}', expected one of: csv, json",
+                s
+            )),
+        }
+    }
+}
+
+/// A word list along with how often words are taken from it relative to the other lists.
+///
+/// Written as `PATH[:WEIGHT[:MIN-MAX]]`, e.g. `keywords.txt:20:2-10`. Either length bound may be
+/// left out, in which case the global word length option applies. A Windows drive prefix such as
+/// `C:\` is part of the path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DictionarySource {
+    pub path: PathBuf,
+    pub weight: u32,
+    pub min_word_len: Option<usize>,
+    pub max_word_len: Option<usize>,
+}
+
+fn parse_length_bound(bound: &str) -> Result<Option<usize>, String> {
+    if bound.is_empty() {
+        return Ok(None);
+    }
+    bound
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("invalid word length '{}'", bound))
+}
+
+/// Length of a Windows drive prefix such as `C:\`, whose colon doesn't separate the weight.
+fn drive_prefix_len(s: &str) -> usize {
+    match s.as_bytes() {
+        [drive, b':', b'\\' | b'/', ..] if drive.is_ascii_alphabetic() => 2,
+        _ => 0,
+    }
+}
+
+impl FromStr for DictionarySource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (drive, rest) = s.split_at(drive_prefix_len(s));
+        let mut parts = rest.splitn(3, ':');
+        let path = PathBuf::from(format!("{}{}", drive, parts.next().unwrap_or_default()));
+
+        let weight = match parts.next() {
+            Some(weight) => weight
+                .parse()
+                .ok()
+                .filter(|&weight| weight > 0)
+                .ok_or_else(|| format!("invalid dictionary weight '{}'", weight))?,
+            None => 1,
+        };
+
+        let (min_word_len, max_word_len) = match parts.next() {
+            Some(lengths) => {
+                let (min, max) = lengths.split_once('-').ok_or_else(|| {
+                    format!("invalid word lengths '{}', expected MIN-MAX", lengths)
+                })?;
+                (parse_length_bound(min)?, parse_length_bound(max)?)
+            }
+            None => (None, None),
+        };
+
+        Ok(DictionarySource {
+            path,
+            weight,
+            min_word_len,
+            max_word_len,
+        })
+    }
+}
+
+impl fmt::Display for DictionarySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if self.weight != 1 {
+            write!(f, " ×{}", self.weight)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum WordlistCommand {
+    /// Show the selected word lists and those in the word list directory
+    List,
+    /// Show word count, word lengths and letter frequencies of a word list
+    Stats {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Make a word list from the words in a text, most frequent first
+    Import {
+        #[structopt(parse(from_os_str))]
+        corpus: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+
+        /// Keep only this many of the most frequent words
+        #[structopt(long)]
+        limit: Option<usize>,
+
+        /// Leave out words that appear fewer times than this
+        #[structopt(long, default_value = "1")]
+        min_count: usize,
+
+        #[structopt(long, default_value = "1")]
+        min_word_len: usize,
+
+        #[structopt(long, default_value = "20")]
+        max_word_len: usize,
+    },
+    /// Write the words of a list that match the given letters and lengths to a new list
+    Filter {
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+
+        /// Only keep words made entirely of these letters
+        #[structopt(long)]
+        letters: Option<String>,
+
+        #[structopt(long, default_value = "1")]
+        min_word_len: usize,
+
+        #[structopt(long, default_value = "20")]
+        max_word_len: usize,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Manage word lists
+    Wordlist(WordlistCommand),
+    /// Show the time between keys for each bigram over recent tests, and how it depends on the
+    /// fingers and hands used with the --layout keyboard layout
+    Stats {
+        /// Number of the slowest bigrams to show
+        #[structopt(long, default_value = "20")]
+        limit: usize,
+    },
+    /// Write the results of every finished test, oldest first
+    Export {
+        /// One of: csv, json
+        #[structopt(long, default_value = "csv")]
+        format: ExportFormat,
+
+        /// File to write to instead of standard output
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Set daily practice goals, and show today's progress and the streak of days they were met
+    Goals {
+        /// Minutes to spend typing each day
+        #[structopt(long)]
+        minutes: Option<f64>,
+
+        /// Number of tests to finish each day
+        #[structopt(long)]
+        tests: Option<usize>,
+
+        /// WPM to reach in at least one test each day
+        #[structopt(long)]
+        wpm: Option<f64>,
+
+        /// Remove every goal
+        #[structopt(long, conflicts_with_all = &["minutes", "tests", "wpm"])]
+        clear: bool,
+
+        /// Hours ahead of UTC that days start at, e.g. -5 or 5.5, for counting daily progress
+        #[structopt(long, allow_hyphen_values = true)]
+        utc_offset: Option<f64>,
+    },
+}
+
 #[derive(Debug, StructOpt)]
 pub struct CliOptions {
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+
+    /// Type snippets of source code from files in this directory instead of random words
+    #[structopt(long, parse(from_os_str))]
+    pub code_dir: Option<PathBuf>,
+
+    /// Type the text in this file instead of random words. Use - to read it from standard input
+    #[structopt(long, parse(from_os_str))]
+    pub text_file: Option<PathBuf>,
+
+    /// Same as --text-file, e.g. `cat article.txt | grumpytype -`
+    #[structopt(name = "FILE", parse(from_os_str), conflicts_with = "text-file")]
+    pub text_file_arg: Option<PathBuf>,
+
+    /// Number of source lines in each code snippet
+    #[structopt(long, default_value = "12")]
+    pub snippet_lines: usize,
+
+    /// Directory for data kept across sessions, such as the trouble words list. Defaults to
+    /// $XDG_DATA_HOME/grumpytype
+    #[structopt(long, parse(from_os_str))]
+    pub data_dir: Option<PathBuf>,
+
+    /// Word list to take random words from, as PATH[:WEIGHT[:MIN-MAX]]. May be given several
+    /// times to mix lists, e.g. `--dictionary english.txt:80 --dictionary keywords.txt:20:2-10`
     #[structopt(
-        long,
+        long = "dictionary",
+        alias = "dictionary-path",
         default_value = "google-10000-english-usa.txt",
-        parse(from_os_str)
+        number_of_values = 1
     )]
-    pub dictionary_path: PathBuf,
+    pub dictionaries: Vec<DictionarySource>,
 
     #[structopt(short, long, default_value = "5")]
     pub display_lines: usize,
 
+    /// Row of the text area, counting from 0, that the line being typed is kept on. Defaults to
+    /// the middle row
+    #[structopt(long)]
+    pub active_line: Option<usize>,
+
+    /// Keyboard layout to practice on a QWERTY system: qwerty, dvorak, colemak, workman, or the
+    /// path to a TOML file with the layout's rows. Shows the layout below the text
+    #[structopt(long)]
+    pub layout: Option<String>,
+
+    /// Show a keyboard below the text that highlights the next key and the finger to press it
+    /// with. Always shown with --layout
+    #[structopt(short, long)]
+    pub keyboard: bool,
+
+    /// One of: normal, stop-on-letter, stop-on-word, confidence
+    #[structopt(short, long, default_value = "normal")]
+    pub input_mode: InputMode,
+
     #[structopt(long, default_value = "3")]
     pub min_word_len: usize,
 
+    /// Only use words made of these letters, e.g. `arstneio`. Falls back to generated words when
+    /// too few words match
+    #[structopt(long)]
+    pub letters: Option<String>,
+
+    /// Order further letters are unlocked in after good tests with --letters
+    #[structopt(long, default_value = DEFAULT_UNLOCK_ORDER)]
+    pub unlock_order: String,
+
+    /// Accuracy percentage a test needs to unlock the next letter
+    #[structopt(long, default_value = "95")]
+    pub unlock_accuracy: f64,
+
+    /// WPM a test needs to unlock the next letter
+    #[structopt(long, default_value = "25")]
+    pub unlock_wpm: f64,
+
+    /// Drill n-grams instead of plain random words: a comma separated list like `th,ing,tion`,
+    /// or `slowest` for the ones that have been slowest to type so far
+    #[structopt(long)]
+    pub ngrams: Option<NgramSelection>,
+
+    /// Percentage of words that are made up, generated to look like words from each word list and
+    /// kept within its length bounds
+    #[structopt(long, default_value = "0")]
+    pub pseudo_words: f64,
+
+    /// Mix previously mistyped words into the test until they are typed correctly several times
+    /// in a row
+    #[structopt(long)]
+    pub practice_missed: bool,
+
     #[structopt(long, default_value = "7")]
     pub max_word_len: usize,
 
     #[structopt(short, long, default_value = "15")]
     pub time_limit: u64,
+
+    /// Print the results of the last finished test to standard output as JSON on exit, instead
+    /// of the summary
+    #[structopt(long)]
+    pub json: bool,
+
+    /// List every word of the last test in the summary printed on exit, with mistyped words
+    /// followed by what was typed
+    #[structopt(long)]
+    pub word_breakdown: bool,
+
+    /// WPM the last test has to reach for the program to exit successfully. Exits with status 2
+    /// when it's missed
+    #[structopt(long)]
+    pub goal_wpm: Option<f64>,
+
+    /// Accuracy percentage the last test has to reach, like --goal-wpm
+    #[structopt(long)]
+    pub goal_accuracy: Option<f64>,
+}
+
+impl CliOptions {
+    /// The file to take the text to type from, if one was given either way.
+    pub fn text_path(&self) -> Option<&PathBuf> {
+        self.text_file.as_ref().or(self.text_file_arg.as_ref())
+    }
+
+    /// The keyboard layout keys are typed on, QWERTY unless another one was chosen.
+    pub fn layout_name(&self) -> &str {
+        self.layout.as_deref().unwrap_or("qwerty")
+    }
+
+    /// Where the text to type comes from, for the run history.
+    pub fn source_description(&self) -> String {
+        match (self.text_path(), &self.code_dir) {
+            (Some(_), _) if self.text_from_stdin() => "standard input".to_string(),
+            (Some(text_path), _) => text_path.display().to_string(),
+            (None, Some(code_dir)) => format!("code: {}", code_dir.display()),
+            (None, None) => self.dictionaries.iter().join(", "),
+        }
+    }
+
+    /// Whether the text to type is read from standard input rather than a file.
+    pub fn text_from_stdin(&self) -> bool {
+        self.text_path().is_some_and(|path| path.as_os_str() == "-")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(
+        path: &str,
+        weight: u32,
+        min_word_len: Option<usize>,
+        max_word_len: Option<usize>,
+    ) -> DictionarySource {
+        DictionarySource {
+            path: PathBuf::from(path),
+            weight,
+            min_word_len,
+            max_word_len,
+        }
+    }
+
+    #[test]
+    fn dictionary_source_path_only() {
+        assert_eq!("words.txt".parse(), Ok(source("words.txt", 1, None, None)));
+    }
+
+    #[test]
+    fn dictionary_source_with_weight_and_lengths() {
+        assert_eq!(
+            "keywords.txt:20".parse(),
+            Ok(source("keywords.txt", 20, None, None))
+        );
+        assert_eq!(
+            "keywords.txt:20:2-10".parse(),
+            Ok(source("keywords.txt", 20, Some(2), Some(10)))
+        );
+        assert_eq!(
+            "keywords.txt:3:-6".parse(),
+            Ok(source("keywords.txt", 3, None, Some(6)))
+        );
+        assert_eq!(
+            "keywords.txt:3:4-".parse(),
+            Ok(source("keywords.txt", 3, Some(4), None))
+        );
+    }
+
+    #[test]
+    fn dictionary_source_bad_weights() {
+        for s in &[
+            "words.txt:0",
+            "words.txt:-2",
+            "words.txt:many",
+            "words.txt:",
+        ] {
+            assert!(
+                s.parse::<DictionarySource>().is_err(),
+                "{} should be rejected",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn dictionary_source_bad_lengths() {
+        for s in &["words.txt:2:5", "words.txt:2:a-5", "words.txt:2:3-b"] {
+            assert!(
+                s.parse::<DictionarySource>().is_err(),
+                "{} should be rejected",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn dictionary_source_windows_paths() {
+        assert_eq!(
+            r"C:\words\keywords.txt".parse(),
+            Ok(source(r"C:\words\keywords.txt", 1, None, None))
+        );
+        assert_eq!(
+            r"C:\words\keywords.txt:20:2-10".parse(),
+            Ok(source(r"C:\words\keywords.txt", 20, Some(2), Some(10)))
+        );
+        assert_eq!(
+            "d:/words.txt:5".parse(),
+            Ok(source("d:/words.txt", 5, None, None))
+        );
+    }
 }
@@ -0,0 +1,189 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const VOWELS: &str = "aeiouy";
+
+/// Makes a word from the given letters, alternating between vowels and consonants where both are
+/// available so that the result can be pronounced.
+pub fn letter_word<R: Rng>(
+    rng: &mut R,
+    letters: &[char],
+    min_word_len: usize,
+    max_word_len: usize,
+) -> String {
+    let (vowels, consonants): (Vec<char>, Vec<char>) =
+        letters.iter().partition(|c| VOWELS.contains(**c));
+
+    let len = rng.gen_range(min_word_len.max(1)..=max_word_len.max(1));
+    let mut use_vowel = rng.gen_bool(0.5);
+    let mut word = String::new();
+    for _ in 0..len {
+        let pool = match (use_vowel, vowels.is_empty(), consonants.is_empty()) {
+            (true, false, _) | (false, _, true) => &vowels,
+            _ => &consonants,
+        };
+        if let Some(c) = pool.choose(rng) {
+            word.push(*c);
+        }
+        use_vowel = !use_vowel;
+    }
+    word
+}
+
+/// Number of preceding letters the next letter of a pseudo-word depends on.
+const MARKOV_ORDER: usize = 2;
+
+/// Marks the start and end of a word in the chain's contexts.
+const WORD_START: char = '^';
+const WORD_END: char = '$';
+
+/// How many words to generate before giving up on finding a new one of the right length.
+const MAX_GENERATION_ATTEMPTS: usize = 50;
+
+/// Generates pronounceable made up words with the letter statistics of a word list, by picking
+/// each letter based on how often it follows the previous few letters in the list.
+pub struct MarkovChain {
+    transitions: HashMap<Vec<char>, (Vec<char>, WeightedIndex<u32>)>,
+    known_words: HashSet<String>,
+}
+
+impl MarkovChain {
+    pub fn train<'a, I: IntoIterator<Item = &'a String>>(words: I) -> Self {
+        let mut counts: HashMap<Vec<char>, HashMap<char, u32>> = HashMap::new();
+        let mut known_words = HashSet::new();
+
+        for word in words {
+            let word = word.to_lowercase();
+            if !word.chars().all(char::is_alphabetic) {
+                continue;
+            }
+
+            let mut context = vec![WORD_START; MARKOV_ORDER];
+            for c in word.chars().chain(std::iter::once(WORD_END)) {
+                *counts
+                    .entry(context.clone())
+                    .or_default()
+                    .entry(c)
+                    .or_default() += 1;
+                context.remove(0);
+                context.push(c);
+            }
+            known_words.insert(word);
+        }
+
+        let transitions = counts
+            .into_iter()
+            .filter_map(|(context, next)| {
+                let (chars, weights): (Vec<char>, Vec<u32>) = next.into_iter().unzip();
+                let weights = WeightedIndex::new(weights).ok()?;
+                Some((context, (chars, weights)))
+            })
+            .collect();
+
+        Self {
+            transitions,
+            known_words,
+        }
+    }
+
+    fn generate_once<R: Rng>(&self, rng: &mut R, max_word_len: usize) -> Option<String> {
+        let mut context = vec![WORD_START; MARKOV_ORDER];
+        let mut word = String::new();
+        loop {
+            let (chars, weights) = self.transitions.get(&context)?;
+            let c = chars[weights.sample(rng)];
+            if c == WORD_END {
+                return Some(word);
+            }
+
+            word.push(c);
+            if word.chars().count() > max_word_len {
+                return None;
+            }
+            context.remove(0);
+            context.push(c);
+        }
+    }
+
+    /// A word within the length bounds that isn't in the training list, or `None` if none turned
+    /// up after several attempts.
+    pub fn generate<R: Rng>(
+        &self,
+        rng: &mut R,
+        min_word_len: usize,
+        max_word_len: usize,
+    ) -> Option<String> {
+        (0..MAX_GENERATION_ATTEMPTS).find_map(|_| {
+            self.generate_once(rng, max_word_len).filter(|word| {
+                word.chars().count() >= min_word_len && !self.known_words.contains(word)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn training_words() -> Vec<String> {
+        words(&[
+            "banana", "bandana", "cabana", "canal", "panama", "nana", "anna", "banal", "canna",
+            "manana", "lana", "alban",
+        ])
+    }
+
+    #[test]
+    fn generated_words_are_within_the_length_bounds() {
+        let chain = MarkovChain::train(&training_words());
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut generated = 0;
+        for _ in 0..200 {
+            if let Some(word) = chain.generate(&mut rng, 4, 6) {
+                let len = word.chars().count();
+                assert!((4..=6).contains(&len), "{} has the wrong length", word);
+                generated += 1;
+            }
+        }
+        assert!(generated > 0);
+    }
+
+    #[test]
+    fn generated_words_are_not_in_the_training_list() {
+        let training_words = training_words();
+        let chain = MarkovChain::train(&training_words);
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..200 {
+            if let Some(word) = chain.generate(&mut rng, 1, 10) {
+                assert!(!training_words.contains(&word), "{} is a real word", word);
+            }
+        }
+    }
+
+    #[test]
+    fn no_word_when_none_can_be_made() {
+        // The only words the chain can make are the ones it was trained on
+        let chain = MarkovChain::train(&words(&["abc"]));
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(chain.generate(&mut rng, 1, 10), None);
+
+        let chain = MarkovChain::train(&training_words());
+        assert_eq!(chain.generate(&mut rng, 20, 30), None);
+    }
+
+    #[test]
+    fn training_skips_words_with_other_characters() {
+        let chain = MarkovChain::train(&words(&["don't", "x-ray"]));
+        let mut rng = StdRng::seed_from_u64(4);
+        assert_eq!(chain.generate(&mut rng, 1, 10), None);
+    }
+}
@@ -0,0 +1,135 @@
+use std::io::Stdout;
+
+use termion::raw::RawTerminal;
+use termion::screen::AlternateScreen;
+use tui::backend::TermionBackend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+
+use crate::keyboard::{KeyPosition, KeyboardLayout};
+use crate::state::State;
+
+use super::spans::span_default;
+
+/// Rows of keys and the space bar, plus the borders.
+pub(super) const KEYBOARD_HEIGHT: u16 = 7;
+
+/// How far each row is shifted right, matching the stagger of a physical keyboard.
+const ROW_OFFSETS: [usize; 4] = [0, 6, 7, 9];
+
+const SPACE_BAR_OFFSET: usize = 18;
+const SPACE_BAR_WIDTH: usize = 24;
+
+fn key_style(key: KeyPosition, next: Option<KeyPosition>, wrong: Option<KeyPosition>) -> Style {
+    let same_key = |other: Option<KeyPosition>| other.is_some_and(|other| key.same_key(other));
+
+    if same_key(wrong) {
+        Style::default()
+            .bg(Color::Red)
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD)
+    } else if same_key(next) {
+        Style::default()
+            .bg(Color::Blue)
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    }
+}
+
+fn key_row<'a>(
+    keys: &[char],
+    row: usize,
+    next: Option<KeyPosition>,
+    wrong: Option<KeyPosition>,
+) -> Spans<'a> {
+    let mut spans = vec![Span::raw(" ".repeat(ROW_OFFSETS[row]))];
+    for (column, c) in keys.iter().enumerate() {
+        let key = KeyPosition::Key {
+            row,
+            column,
+            shifted: false,
+        };
+        spans.push(Span::styled(
+            format!(" {} ", c),
+            key_style(key, next, wrong),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    Spans::from(spans)
+}
+
+fn space_bar<'a>(next: Option<KeyPosition>, wrong: Option<KeyPosition>) -> Spans<'a> {
+    let label = format!("{:^width$}", "space", width = SPACE_BAR_WIDTH);
+    Spans::from(vec![
+        Span::raw(" ".repeat(SPACE_BAR_OFFSET)),
+        Span::styled(label, key_style(KeyPosition::Space, next, wrong)),
+    ])
+}
+
+/// Describes the next key and the finger to press it with.
+fn hint(
+    layout: &KeyboardLayout,
+    state: &State,
+    next_char: Option<char>,
+    next: Option<KeyPosition>,
+) -> String {
+    match (next_char, next) {
+        (Some(c), Some(position)) => {
+            let key = match position {
+                KeyPosition::Space => "space".to_string(),
+                KeyPosition::Enter => "enter".to_string(),
+                KeyPosition::Tab => "tab".to_string(),
+                KeyPosition::Key { .. } => c.to_string(),
+            };
+            let shift = match position {
+                KeyPosition::Key { shifted: true, .. } => " + shift",
+                _ => "",
+            };
+            format!("Next: {}, {}{}", key, layout.finger(position).name(), shift)
+        }
+        (Some(c), None) => format!("Next: {}", c),
+        (None, _) if !state.current_word.is_empty() => "Fix the mistake with backspace".to_string(),
+        (None, _) => String::new(),
+    }
+}
+
+/// Draws the keyboard, highlighting the key to press next and flashing `wrong_char` if it was
+/// just typed by mistake.
+pub(super) fn draw_keyboard(
+    frame: &mut Frame<TermionBackend<AlternateScreen<RawTerminal<Stdout>>>>,
+    layout: &KeyboardLayout,
+    state: &State,
+    wrong_char: Option<char>,
+    area: Rect,
+) {
+    let next_char = state.next_expected_char();
+    let next = next_char.and_then(|c| layout.position(c));
+    let wrong = wrong_char.and_then(|c| layout.position(c));
+
+    let mut rows: Vec<Spans> = layout
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(row, keys)| key_row(keys, row, next, wrong))
+        .collect();
+    rows.push(space_bar(next, wrong));
+
+    let mut title = vec![Span::styled(
+        layout.name.as_str(),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    let hint = hint(layout, state, next_char, next);
+    if !hint.is_empty() {
+        title.push(span_default(format!(" | {}", hint)));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Spans::from(title));
+    frame.render_widget(Paragraph::new(rows).block(block), area);
+}
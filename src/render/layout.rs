@@ -0,0 +1,148 @@
+use std::cmp::max;
+use std::ops::Range;
+
+use itertools::{EitherOrBoth, Itertools};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::state::State;
+
+use super::text::{display_char, indent_display, typed_word};
+
+/// A piece of a word placed on a row. Words are only split across rows when they are wider than
+/// a whole row.
+pub(super) struct Segment {
+    pub word_index: usize,
+    /// Range of displayed characters of the word that are on this row.
+    pub chars: Range<usize>,
+    pub x: usize,
+}
+
+#[derive(Default)]
+pub(super) struct Row {
+    /// Indentation is only shown on the first row of a line.
+    pub indent: String,
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Clone, Copy)]
+pub(super) struct CursorPosition {
+    pub row: usize,
+    pub x: usize,
+}
+
+/// Where each word of the text goes on screen for a given width. Rendering and cursor placement
+/// both go through this, so they always agree.
+pub(super) struct TextLayout {
+    pub rows: Vec<Row>,
+    pub cursor: CursorPosition,
+}
+
+/// Widths of the characters shown for a word, which are the typed characters followed by any
+/// expected characters that haven't been typed yet.
+fn displayed_char_widths(typed: &str, expected: &str) -> Vec<usize> {
+    typed
+        .chars()
+        .zip_longest(expected.chars())
+        .map(|entry| match entry {
+            EitherOrBoth::Left(c) | EitherOrBoth::Both(c, _) | EitherOrBoth::Right(c) => {
+                display_char(c).width().unwrap_or(0)
+            }
+        })
+        .collect()
+}
+
+pub(super) fn layout_text(state: &State, width: u16) -> TextLayout {
+    let width = max(width as usize, 1);
+    let num_typed_words = state.typed_words.len();
+
+    let mut rows: Vec<Row> = Vec::new();
+    let mut cursor = None;
+    let mut word_index = 0;
+
+    for line in &state.lines {
+        let mut indent = indent_display(&line.indent);
+        if indent.width() >= width {
+            indent.clear();
+        }
+        let mut x = indent.width();
+        rows.push(Row {
+            indent,
+            segments: Vec::new(),
+        });
+
+        for expected in &line.words {
+            let typed = typed_word(state, word_index);
+            let char_widths = displayed_char_widths(typed, expected);
+            let word_width: usize = char_widths.iter().sum();
+
+            if !rows[rows.len() - 1].segments.is_empty() {
+                if x + 1 + word_width <= width {
+                    // Leave room for the space between words
+                    x += 1;
+                } else {
+                    rows.push(Row::default());
+                    x = 0;
+                }
+            }
+
+            let is_current_word = word_index == num_typed_words;
+            let cursor_char = typed.chars().count();
+            let mut segment = Segment {
+                word_index,
+                chars: 0..0,
+                x,
+            };
+
+            for (i, char_width) in char_widths.into_iter().enumerate() {
+                if x + char_width > width && x > 0 {
+                    // The word is wider than a whole row, so it has to be broken up
+                    if !segment.chars.is_empty() {
+                        rows.last_mut().unwrap().segments.push(segment);
+                    }
+                    rows.push(Row::default());
+                    x = 0;
+                    segment = Segment {
+                        word_index,
+                        chars: i..i,
+                        x,
+                    };
+                }
+
+                if is_current_word && i == cursor_char {
+                    cursor = Some(CursorPosition {
+                        row: rows.len() - 1,
+                        x,
+                    });
+                }
+
+                x += char_width;
+                segment.chars.end = i + 1;
+            }
+
+            if is_current_word && cursor.is_none() {
+                cursor = Some(if x < width {
+                    CursorPosition {
+                        row: rows.len() - 1,
+                        x,
+                    }
+                } else {
+                    CursorPosition {
+                        row: rows.len(),
+                        x: 0,
+                    }
+                });
+            }
+
+            rows.last_mut().unwrap().segments.push(segment);
+            word_index += 1;
+        }
+    }
+
+    // Once the whole text has been typed, the cursor goes below it
+    let cursor = cursor.unwrap_or(CursorPosition {
+        row: rows.len(),
+        x: 0,
+    });
+
+    TextLayout { rows, cursor }
+}
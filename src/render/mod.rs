@@ -1,6 +1,10 @@
-mod cursor;
+mod keyboard;
+mod layout;
+mod results;
+mod review;
 mod spans;
 mod text;
+mod viewport;
 
 use std::cmp::min;
 use std::io;
@@ -12,34 +16,43 @@ use termion::event::Key;
 use termion::raw::{IntoRawMode, RawTerminal};
 use termion::screen::AlternateScreen;
 use tui::backend::TermionBackend;
-use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::Modifier;
 use tui::style::Style;
 use tui::text::Span;
 use tui::text::Spans;
-use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::widgets::{Block, Borders, Clear, Paragraph};
 use tui::Frame;
 use tui::Terminal;
 
-use crate::dictionary::Dictionary;
+use crate::drill::LetterDrill;
 use crate::error::ApplicationError;
+use crate::goals::GoalProgress;
 use crate::input::handle_key;
+use crate::keyboard::KeyboardLayout;
+use crate::opt::CliOptions;
 use crate::render::spans::span_correct;
 use crate::render::spans::span_default;
 use crate::render::spans::span_incorrect;
+use crate::source::TextSource;
 use crate::state::State;
 use crate::stats::Stats;
 
-use self::cursor::get_cursor_position;
-use self::cursor::CursorPosition;
-use self::text::{render_text, word_display_len};
+use self::keyboard::{draw_keyboard, KEYBOARD_HEIGHT};
+use self::layout::{layout_text, TextLayout};
+use self::results::draw_results;
+use self::review::draw_review;
+use self::text::render_text;
+use self::viewport::Viewport;
+
+/// How long a key typed by mistake stays highlighted on the keyboard.
+const WRONG_KEY_FLASH: Duration = Duration::from_millis(300);
+
+const PAUSE_OVERLAY_WIDTH: u16 = 24;
+const PAUSE_OVERLAY_HEIGHT: u16 = 5;
 
 fn get_typing_seconds(state: &State) -> Option<u64> {
-    state
-        .start_time
-        .and_then(|start_time| Instant::now().checked_duration_since(start_time))
-        .as_ref()
-        .map(Duration::as_secs)
+    state.elapsed().as_ref().map(Duration::as_secs)
 }
 
 fn handle_timer(state: &mut State, time_limit_sec: u64) {
@@ -50,24 +63,7 @@ fn handle_timer(state: &mut State, time_limit_sec: u64) {
     }
 }
 
-fn drop_first_line(state: &mut State, text_area_without_border: &Rect) {
-    let all_words = &state.all_words;
-
-    let mut n_words = 0;
-    let mut current_line_len = all_words[0].len() as u16;
-
-    while current_line_len <= text_area_without_border.width {
-        let expected_word = &all_words[n_words];
-        let typed_word = &state.typed_words[n_words];
-        n_words += 1;
-        current_line_len += word_display_len(expected_word, typed_word) as u16 + 1;
-    }
-
-    state.all_words = all_words[n_words..].to_vec();
-    state.typed_words = state.typed_words[n_words..].to_vec();
-}
-
-fn ui_layout(area: Rect, text_area_height: u16) -> Vec<Rect> {
+fn ui_layout(area: Rect, text_area_height: u16, keyboard_height: u16) -> Vec<Rect> {
     Layout::default()
         .direction(Direction::Vertical)
         .horizontal_margin(8)
@@ -75,6 +71,7 @@ fn ui_layout(area: Rect, text_area_height: u16) -> Vec<Rect> {
             Constraint::Length(1),
             // Add 2 for the borders
             Constraint::Length(text_area_height + 2),
+            Constraint::Length(keyboard_height),
             Constraint::Length(1),
             Constraint::Min(0),
         ])
@@ -98,13 +95,13 @@ fn draw_timer(
 fn draw_text_area(
     frame: &mut Frame<TermionBackend<AlternateScreen<RawTerminal<Stdout>>>>,
     state: &State,
+    layout: &TextLayout,
+    viewport: &Viewport,
     area: Rect,
 ) {
-    let spans = render_text(state);
+    let spans = render_text(state, layout, viewport.rows());
     let block = Block::default().borders(Borders::ALL);
-    let paragraph = Paragraph::new(spans)
-        .block(block)
-        .wrap(Wrap { trim: false });
+    let paragraph = Paragraph::new(spans).block(block);
     frame.render_widget(paragraph, area);
 }
 
@@ -115,6 +112,10 @@ fn draw_instructions(
     let instructions = Spans::from(vec![
         span_correct("Retry: "),
         span_default("Ctrl-R | "),
+        span_correct("Same text: "),
+        span_default("Ctrl-T | "),
+        span_correct("Pause: "),
+        span_default("Esc | "),
         span_incorrect("Quit: "),
         span_default("Ctrl-C"),
     ]);
@@ -122,37 +123,54 @@ fn draw_instructions(
     frame.render_widget(paragraph, area);
 }
 
-fn draw_cursor(
+fn draw_pause_overlay(
     frame: &mut Frame<TermionBackend<AlternateScreen<RawTerminal<Stdout>>>>,
-    state: &State,
-    text_area_without_border: Rect,
-) -> CursorPosition {
-    let cursor_position = get_cursor_position(state, text_area_without_border);
-    frame.set_cursor(cursor_position.x, cursor_position.y);
-    cursor_position
+    area: Rect,
+) {
+    let overlay_area = Rect {
+        x: area.x + area.width.saturating_sub(PAUSE_OVERLAY_WIDTH) / 2,
+        y: area.y + area.height.saturating_sub(PAUSE_OVERLAY_HEIGHT) / 2,
+        width: min(PAUSE_OVERLAY_WIDTH, area.width),
+        height: min(PAUSE_OVERLAY_HEIGHT, area.height),
+    };
+
+    let text = vec![
+        Spans::from(Span::styled(
+            "Paused",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+        Spans::from(vec![span_correct("Resume: "), span_default("Esc")]),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL))
+        .alignment(Alignment::Center);
+
+    frame.render_widget(Clear, overlay_area);
+    frame.render_widget(paragraph, overlay_area);
 }
 
-fn drop_line_if_necessary(
-    state: &mut State,
-    cursor_position: CursorPosition,
-    last_cursor_x: u16,
-    num_text_lines_to_show: usize,
+fn draw_cursor(
+    frame: &mut Frame<TermionBackend<AlternateScreen<RawTerminal<Stdout>>>>,
+    layout: &TextLayout,
+    viewport: &Viewport,
     text_area_without_border: Rect,
 ) {
-    if last_cursor_x > cursor_position.x
-        && cursor_position.y as usize
-            > (num_text_lines_to_show / 2) + text_area_without_border.y as usize
-    {
-        drop_first_line(state, &text_area_without_border);
+    let cursor_position = layout.cursor;
+    if viewport.rows().contains(&cursor_position.row) {
+        frame.set_cursor(
+            text_area_without_border.x + cursor_position.x as u16,
+            text_area_without_border.y + (cursor_position.row - viewport.first_row) as u16,
+        );
     }
 }
 
 pub fn render_typing_test(
     state: &mut State,
-    dictionary: &mut Dictionary,
+    text_source: &mut TextSource,
+    keyboard_layout: Option<&KeyboardLayout>,
     input_receiver: &Receiver<Key>,
-    num_text_lines_to_show: usize,
-    time_limit_sec: u64,
+    opt: &CliOptions,
 ) -> Result<(), ApplicationError> {
     let stdout = io::stdout()
         .into_raw_mode()
@@ -163,57 +181,80 @@ pub fn render_typing_test(
 
     terminal.clear().map_err(ApplicationError::TerminalClear)?;
 
-    let mut last_cursor_x = 1;
+    // The last key typed by mistake and when, for flashing it on the keyboard
+    let mut wrong_key: Option<(char, Instant)> = None;
 
     loop {
         if let Ok(key) = input_receiver.recv_timeout(Duration::from_millis(10)) {
+            let key = match keyboard_layout {
+                Some(keyboard_layout) => keyboard_layout.remap_key(key),
+                None => key,
+            };
+            if let Key::Char(c) = key {
+                if !state.is_paused() && state.next_expected_char() != Some(c) {
+                    wrong_key = Some((c, Instant::now()));
+                }
+            }
             handle_key(state, key);
         }
 
-        handle_timer(state, time_limit_sec);
+        handle_timer(state, opt.time_limit);
 
-        if state.quit || state.complete || state.retry {
+        if state.quit || state.complete || state.retry || state.restart_same_text {
             terminal.clear().map_err(ApplicationError::TerminalClear)?;
             break;
         }
 
         terminal
-            .draw(|mut f| {
-                dictionary.load_words(state, 300);
+            .draw(|f| {
+                text_source.load_text(state, 300);
 
                 let size = f.size();
 
-                let text_area_height = min(num_text_lines_to_show, size.height as usize);
+                let text_area_height = min(opt.display_lines, size.height as usize);
 
-                let layout = ui_layout(size, text_area_height as u16);
+                let keyboard_height = if keyboard_layout.is_some() {
+                    KEYBOARD_HEIGHT
+                } else {
+                    0
+                };
+                let layout = ui_layout(size, text_area_height as u16, keyboard_height);
 
                 let timer_area = layout[0];
                 let text_area_and_border = layout[1];
-                let instructions_area = layout[2];
+                let keyboard_area = layout[2];
+                let instructions_area = layout[3];
 
-                draw_timer(&mut f, state, time_limit_sec, timer_area);
-
-                draw_text_area(&mut f, state, text_area_and_border);
-
-                draw_instructions(&mut f, instructions_area);
+                draw_timer(f, state, opt.time_limit, timer_area);
 
                 let text_area_without_border = Rect {
                     x: text_area_and_border.x + 1,
                     y: text_area_and_border.y + 1,
-                    width: text_area_and_border.width - 2,
-                    height: text_area_and_border.height - 2,
+                    width: text_area_and_border.width.saturating_sub(2),
+                    height: text_area_and_border.height.saturating_sub(2),
                 };
 
-                let cursor_position = draw_cursor(&mut f, state, text_area_without_border);
-                drop_line_if_necessary(
-                    state,
-                    cursor_position,
-                    last_cursor_x,
-                    num_text_lines_to_show,
-                    text_area_without_border,
-                );
+                let layout = layout_text(state, text_area_without_border.width);
+                let num_rows = text_area_without_border.height as usize;
+                let active_row = opt.active_line.unwrap_or(num_rows / 2);
+                let viewport = Viewport::around_cursor(&layout, num_rows, active_row);
+
+                draw_text_area(f, state, &layout, &viewport, text_area_and_border);
+
+                if let Some(keyboard_layout) = keyboard_layout {
+                    let wrong_char = wrong_key
+                        .filter(|(_, typed_at)| typed_at.elapsed() < WRONG_KEY_FLASH)
+                        .map(|(c, _)| c);
+                    draw_keyboard(f, keyboard_layout, state, wrong_char, keyboard_area);
+                }
+
+                draw_instructions(f, instructions_area);
 
-                last_cursor_x = cursor_position.x;
+                if state.is_paused() {
+                    draw_pause_overlay(f, text_area_and_border);
+                }
+
+                draw_cursor(f, &layout, &viewport, text_area_without_border);
             })
             .map_err(ApplicationError::TerminalDraw)?;
     }
@@ -223,83 +264,65 @@ pub fn render_typing_test(
 
 pub fn render_stats(
     state: &mut State,
+    stats: &Stats,
+    drill: Option<&LetterDrill>,
+    goals: Option<&GoalProgress>,
     input_receiver: &Receiver<Key>,
-    time_limit_sec: u64,
+    opt: &CliOptions,
 ) -> Result<(), ApplicationError> {
-    let maybe_stats = Stats::from_counters(&state.counters, time_limit_sec);
-
-    if let Some(Stats { accuracy, wpm }) = maybe_stats {
-        let stdout = io::stdout()
-            .into_raw_mode()
-            .map_err(ApplicationError::RawMode)?;
-        let stdout = AlternateScreen::from(stdout);
-        let backend = TermionBackend::new(stdout);
-        let mut terminal =
-            Terminal::new(backend).map_err(ApplicationError::TerminalInstantiation)?;
-
-        terminal.clear().map_err(ApplicationError::TerminalClear)?;
-
-        loop {
-            if let Ok(key) = input_receiver.recv_timeout(Duration::from_millis(10)) {
-                if let Key::Ctrl('c') = key {
+    let has_mistakes = !stats.mistyped_words.is_empty();
+
+    let stdout = io::stdout()
+        .into_raw_mode()
+        .map_err(ApplicationError::RawMode)?;
+    let stdout = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).map_err(ApplicationError::TerminalInstantiation)?;
+
+    terminal.clear().map_err(ApplicationError::TerminalClear)?;
+
+    let mut reviewing = false;
+    let mut review_scroll: usize = 0;
+
+    loop {
+        if let Ok(key) = input_receiver.recv_timeout(Duration::from_millis(10)) {
+            match key {
+                Key::Ctrl('c') => {
                     state.quit = true;
                     break;
                 }
-                if let Key::Char('r') = key {
+                Key::Char('p') if has_mistakes => {
+                    state.practice_mistakes = true;
+                    break;
+                }
+                Key::Esc if reviewing => reviewing = false,
+                Key::Up | Key::Char('k') if reviewing => {
+                    review_scroll = review_scroll.saturating_sub(1);
+                }
+                Key::Down | Key::Char('j') if reviewing => review_scroll += 1,
+                Key::Char('m') => reviewing = true,
+                Key::Char('r') => break,
+                Key::Char('t') => {
+                    state.restart_same_text = true;
                     break;
                 }
+                _ => {}
             }
-
-            terminal
-                .draw(|f| {
-                    let layout_outer = Layout::default()
-                        .direction(Direction::Vertical)
-                        .horizontal_margin(8)
-                        .constraints([Constraint::Length(8), Constraint::Min(0)])
-                        .split(f.size());
-
-                    let borders = Block::default().borders(Borders::ALL);
-                    f.render_widget(borders, layout_outer[0]);
-
-                    let layout = Layout::default()
-                        .direction(Direction::Vertical)
-                        .horizontal_margin(2)
-                        .vertical_margin(1)
-                        .constraints([
-                            Constraint::Length(1),
-                            Constraint::Length(1),
-                            Constraint::Length(1),
-                            Constraint::Length(1),
-                            Constraint::Length(1),
-                            Constraint::Length(1),
-                        ])
-                        .split(layout_outer[0]);
-
-                    let title =
-                        Span::styled("Stats", Style::default().add_modifier(Modifier::BOLD));
-                    let title = Paragraph::new(title);
-
-                    let accuracy = Span::from(format!("Accuracy: {:.2}%", accuracy * 100.0));
-                    let accuracy = Paragraph::new(accuracy);
-
-                    let wpm = Span::from(format!("WPM: {:.2}", wpm));
-                    let wpm = Paragraph::new(wpm);
-
-                    let instructions = Spans::from(vec![
-                        span_correct("Go again: "),
-                        span_default("R | "),
-                        span_incorrect("Quit: "),
-                        span_default("Ctrl-C"),
-                    ]);
-                    let instructions = Paragraph::new(instructions);
-
-                    f.render_widget(title, layout[0]);
-                    f.render_widget(accuracy, layout[2]);
-                    f.render_widget(wpm, layout[3]);
-                    f.render_widget(instructions, layout[5]);
-                })
-                .map_err(ApplicationError::TerminalDraw)?;
         }
+
+        terminal
+            .draw(|f| {
+                let area = Layout::default()
+                    .horizontal_margin(8)
+                    .constraints([Constraint::Min(0)])
+                    .split(f.size())[0];
+                if reviewing {
+                    draw_review(f, state, &mut review_scroll, area);
+                } else {
+                    draw_results(f, stats, drill, goals, opt, area);
+                }
+            })
+            .map_err(ApplicationError::TerminalDraw)?;
     }
 
     Ok(())
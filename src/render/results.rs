@@ -0,0 +1,372 @@
+use std::cmp::max;
+use std::io::Stdout;
+
+use itertools::Itertools;
+use termion::raw::RawTerminal;
+use termion::screen::AlternateScreen;
+use tui::backend::TermionBackend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+
+use crate::drill::LetterDrill;
+use crate::fingers::{usage_header, usage_rows};
+use crate::goals::{plural, GoalProgress};
+use crate::opt::CliOptions;
+use crate::stats::Stats;
+
+use super::spans::{span_correct, span_default, span_incorrect};
+use super::text::render_word;
+
+/// Terminals at least this wide show the summary sections side by side.
+const WIDE_LAYOUT_MIN_WIDTH: u16 = 100;
+
+fn speed_lines(stats: &Stats) -> Vec<Spans<'_>> {
+    let mut lines = Vec::new();
+
+    if stats.attempted_word_count == 0 {
+        lines.push(Spans::from(span_incorrect("No words completed")));
+    }
+
+    lines.push(Spans::from(Span::styled(
+        format!("WPM: {:.2}", stats.net_wpm),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Spans::from(format!("Raw WPM: {:.2}", stats.raw_wpm)));
+    lines.push(Spans::from(format!("CPM: {:.2}", stats.cpm)));
+
+    let consistency = match stats.consistency {
+        Some(variation) => format!(
+            "Consistency: {:.1}% (CV {:.2})",
+            (1.0 - variation).max(0.0) * 100.0,
+            variation
+        ),
+        None => "Consistency: -".to_string(),
+    };
+    lines.push(Spans::from(consistency));
+    lines.push(Spans::from(format!(
+        "Idle time: {}s",
+        stats.idle_time.as_secs()
+    )));
+
+    lines
+}
+
+fn accuracy_lines(stats: &Stats) -> Vec<Spans<'_>> {
+    let accuracy = match stats.accuracy {
+        Some(accuracy) => format!("Accuracy: {:.2}%", accuracy * 100.0),
+        None => "Accuracy: -".to_string(),
+    };
+
+    let mut lines = vec![
+        Spans::from(accuracy),
+        Spans::from(format!(
+            "Words: {}/{} correct",
+            stats.correct_word_count, stats.attempted_word_count
+        )),
+        Spans::from(vec![
+            Span::from("Characters: "),
+            span_correct(stats.chars.correct.to_string()),
+            span_default("/"),
+            span_incorrect(stats.chars.incorrect.to_string()),
+            span_default("/"),
+            span_incorrect(stats.chars.extra.to_string()),
+            span_default("/"),
+            span_incorrect(stats.chars.missed.to_string()),
+        ]),
+        Spans::from(span_default("  (correct/incorrect/extra/missed)")),
+        Spans::from(format!("Keystrokes: {}", stats.typed_char_count)),
+        Spans::from(format!("Backspaces: {}", stats.backspace_count)),
+        Spans::from(format!("Word deletions: {}", stats.word_deletion_count)),
+    ];
+
+    if let Some(partial) = &stats.partial_word {
+        let mut spans = vec![Span::from("Unfinished: ")];
+        spans.extend(render_word(&partial.typed, &partial.expected, false));
+        spans.push(Span::from(format!(
+            " ({}/{})",
+            partial.correct_char_count,
+            partial.expected.chars().count()
+        )));
+        lines.push(Spans::from(spans));
+    }
+
+    lines
+}
+
+fn settings_lines<'a>(opt: &'a CliOptions, drill: Option<&LetterDrill>) -> Vec<Spans<'a>> {
+    let mut lines = vec![
+        Spans::from(format!("Mode: {}", opt.input_mode)),
+        Spans::from(format!("Time limit: {}s", opt.time_limit)),
+    ];
+
+    match (opt.text_path(), &opt.code_dir) {
+        (Some(_), _) if opt.text_from_stdin() => {
+            lines.push(Spans::from("Text: standard input"));
+        }
+        (Some(text_path), _) => {
+            lines.push(Spans::from(format!("Text: {}", text_path.display())));
+        }
+        (None, Some(code_dir)) => {
+            lines.push(Spans::from(format!("Code: {}", code_dir.display())));
+            lines.push(Spans::from(format!("Snippet lines: {}", opt.snippet_lines)));
+        }
+        (None, None) => {
+            lines.push(Spans::from(format!(
+                "Word length: {}-{}",
+                opt.min_word_len, opt.max_word_len
+            )));
+            lines.push(Spans::from(format!(
+                "Dictionary: {}",
+                opt.dictionaries.iter().join(", ")
+            )));
+        }
+    }
+
+    if opt.pseudo_words > 0.0 {
+        lines.push(Spans::from(format!("Made up words: {}%", opt.pseudo_words)));
+    }
+
+    if let Some(ngrams) = &opt.ngrams {
+        lines.push(Spans::from(format!("N-grams: {}", ngrams)));
+    }
+
+    if let Some(drill) = drill {
+        lines.push(Spans::from(format!(
+            "Letters: {}",
+            drill.letters().iter().collect::<String>()
+        )));
+        match (drill.just_unlocked(), drill.next_letter()) {
+            (Some(unlocked), _) => {
+                lines.push(Spans::from(span_correct(format!("Unlocked: {}", unlocked))))
+            }
+            (None, Some(next)) => lines.push(Spans::from(format!(
+                "Next: {} at {:.0}% and {:.0} WPM",
+                next,
+                drill.min_accuracy() * 100.0,
+                drill.min_wpm()
+            ))),
+            (None, None) => {}
+        }
+    }
+
+    lines
+}
+
+fn goal_lines(goals: &GoalProgress) -> Vec<Spans<'static>> {
+    let mut lines: Vec<Spans> = goals
+        .lines()
+        .into_iter()
+        .map(|(line, met)| {
+            if met {
+                Spans::from(span_correct(line))
+            } else {
+                Spans::from(line)
+            }
+        })
+        .collect();
+    lines.push(Spans::from(format!(
+        "Goal streak: {}",
+        plural(goals.current_streak, "day")
+    )));
+    lines
+}
+
+fn mistyped_word_lines(stats: &Stats, max_lines: usize) -> Vec<Spans<'_>> {
+    let mistyped_words = &stats.mistyped_words;
+
+    if mistyped_words.is_empty() {
+        return vec![Spans::from(span_correct("None"))];
+    }
+
+    // Leave room for the line saying how many more words there are
+    let shown = if mistyped_words.len() > max_lines {
+        max_lines.saturating_sub(1)
+    } else {
+        mistyped_words.len()
+    };
+
+    let mut lines: Vec<Spans> = mistyped_words[..shown]
+        .iter()
+        .map(|word| {
+            let mut spans = vec![span_default(word.expected.as_str()), span_default(" → ")];
+            spans.extend(render_word(&word.typed, &word.expected, false));
+            Spans::from(spans)
+        })
+        .collect();
+
+    if shown < mistyped_words.len() {
+        lines.push(Spans::from(span_default(format!(
+            "... and {} more",
+            mistyped_words.len() - shown
+        ))));
+    }
+
+    lines
+}
+
+fn transition_lines(stats: &Stats, max_lines: usize) -> Vec<Spans<'_>> {
+    if stats.bigram_latencies.is_empty() {
+        return vec![Spans::from(span_default("Not enough keystrokes"))];
+    }
+
+    let mut transition_lines: Vec<Spans> = stats
+        .transitions
+        .iter()
+        .map(|transition| {
+            Spans::from(format!(
+                "{}: {:.0}%, {}ms",
+                transition.transition.name(),
+                transition.share * 100.0,
+                transition.average.as_millis()
+            ))
+        })
+        .collect();
+    if !transition_lines.is_empty() {
+        transition_lines.insert(0, Spans::from(""));
+    }
+
+    // Leave room for the heading and the transitions
+    let shown = max_lines.saturating_sub(transition_lines.len() + 1);
+    let mut lines = vec![Spans::from(span_default("Slowest (average / median):"))];
+    lines.extend(stats.bigram_latencies.iter().take(shown).map(|latency| {
+        Spans::from(vec![
+            span_incorrect(latency.bigram.as_str()),
+            Span::from(format!(
+                " {} / {}ms ×{}",
+                latency.average.as_millis(),
+                latency.median.as_millis(),
+                latency.count
+            )),
+        ])
+    }));
+    lines.extend(transition_lines);
+    lines
+}
+
+fn finger_lines(stats: &Stats) -> Vec<Spans<'_>> {
+    let usage = &stats.finger_usage;
+    if usage.keystroke_count() == 0 {
+        return vec![Spans::from(span_default("Not enough keystrokes"))];
+    }
+
+    let mut lines = vec![Spans::from(span_default(usage_header()))];
+    lines.extend(usage_rows(usage).into_iter().map(Spans::from));
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(match usage.same_finger_share() {
+        Some(share) => format!(
+            "Same finger: {:.1}% ({})",
+            share * 100.0,
+            usage.same_finger_bigrams
+        ),
+        None => "Same finger: -".to_string(),
+    }));
+    lines.push(Spans::from(format!("Row jumps: {}", usage.row_jumps)));
+    lines
+}
+
+fn section<'a>(title: &'a str, lines: Vec<Spans<'a>>) -> Paragraph<'a> {
+    let title = Span::styled(title, Style::default().add_modifier(Modifier::BOLD));
+    Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+pub(super) fn draw_results(
+    frame: &mut Frame<TermionBackend<AlternateScreen<RawTerminal<Stdout>>>>,
+    stats: &Stats,
+    drill: Option<&LetterDrill>,
+    goals: Option<&GoalProgress>,
+    opt: &CliOptions,
+    area: Rect,
+) {
+    let mut test_lines = settings_lines(opt, drill);
+    if let Some(goals) = goals {
+        test_lines.extend(goal_lines(goals));
+    }
+
+    let summary = vec![
+        ("Speed", speed_lines(stats)),
+        ("Accuracy", accuracy_lines(stats)),
+        ("Test", test_lines),
+    ];
+
+    let instructions = Paragraph::new(Spans::from(vec![
+        span_correct("Go again: "),
+        span_default("R | "),
+        span_correct("Same text: "),
+        span_default("T | "),
+        span_correct("Review: "),
+        span_default("M | "),
+        span_correct("Practice mistakes: "),
+        span_default("P | "),
+        span_incorrect("Quit: "),
+        span_default("Ctrl-C"),
+    ]));
+
+    // Add 2 for the borders
+    let (summary_areas, details_area, instructions_area) = if area.width >= WIDE_LAYOUT_MIN_WIDTH {
+        let summary_height = summary
+            .iter()
+            .fold(0, |height, (_, lines)| max(height, lines.len()))
+            + 2;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(summary_height as u16),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Ratio(1, 3),
+                Constraint::Ratio(1, 3),
+                Constraint::Ratio(1, 3),
+            ])
+            .split(layout[0]);
+
+        (columns, layout[1], layout[2])
+    } else {
+        let mut constraints: Vec<Constraint> = summary
+            .iter()
+            .map(|(_, lines)| Constraint::Length(lines.len() as u16 + 2))
+            .collect();
+        constraints.push(Constraint::Min(3));
+        constraints.push(Constraint::Length(1));
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        (layout[..3].to_vec(), layout[3], layout[4])
+    };
+
+    for ((title, lines), section_area) in summary.into_iter().zip(summary_areas) {
+        frame.render_widget(section(title, lines), section_area);
+    }
+
+    let detail_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(details_area);
+    let max_lines = details_area.height.saturating_sub(2) as usize;
+
+    let mistyped_lines = mistyped_word_lines(stats, max_lines);
+    frame.render_widget(section("Mistyped words", mistyped_lines), detail_areas[0]);
+    let transition_lines = transition_lines(stats, max_lines);
+    frame.render_widget(
+        section("Key transitions", transition_lines),
+        detail_areas[1],
+    );
+    frame.render_widget(section("Fingers", finger_lines(stats)), detail_areas[2]);
+
+    frame.render_widget(instructions, instructions_area);
+}
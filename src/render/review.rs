@@ -0,0 +1,154 @@
+use std::cmp::{max, min};
+use std::io::Stdout;
+
+use termion::raw::RawTerminal;
+use termion::screen::AlternateScreen;
+use tui::backend::TermionBackend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+use unicode_width::UnicodeWidthChar;
+
+use crate::state::State;
+
+use super::spans::{span_correct, span_default, span_incorrect};
+use super::text::{display_char, render_word, typed_word};
+
+/// A row of expected words, with the attempts at any mistyped ones on the row below.
+#[derive(Default)]
+struct ReviewRow<'a> {
+    expected: Vec<Span<'a>>,
+    typed: Vec<Span<'a>>,
+    width: usize,
+}
+
+fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(Span::width).sum()
+}
+
+impl<'a> ReviewRow<'a> {
+    fn push_word(&mut self, expected: &'a str, typed: &'a str) {
+        if self.width > 0 {
+            self.expected.push(Span::raw(" "));
+            self.typed.push(Span::raw(" "));
+            self.width += 1;
+        }
+
+        let expected_text: String = expected.chars().map(display_char).collect();
+        let (expected_spans, typed_spans) = if typed == expected {
+            (vec![span_correct(expected_text)], Vec::new())
+        } else {
+            // Only show what was actually typed, since the expected word is right above it
+            let typed_spans = render_word(typed, expected, false)
+                .into_iter()
+                .take(typed.chars().count())
+                .collect();
+            (vec![span_incorrect(expected_text)], typed_spans)
+        };
+
+        let word_width = max(spans_width(&expected_spans), spans_width(&typed_spans));
+        for (row, spans) in [
+            (&mut self.expected, expected_spans),
+            (&mut self.typed, typed_spans),
+        ] {
+            let padding = word_width - spans_width(&spans);
+            row.extend(spans);
+            row.push(Span::raw(" ".repeat(padding)));
+        }
+        self.width += word_width;
+    }
+
+    fn into_spans(self) -> [Spans<'a>; 2] {
+        [Spans::from(self.expected), Spans::from(self.typed)]
+    }
+}
+
+fn word_width(expected: &str, typed: &str) -> usize {
+    let chars_width = |text: &str| -> usize {
+        text.chars()
+            .map(|c| display_char(c).width().unwrap_or(0))
+            .sum()
+    };
+    max(chars_width(expected), chars_width(typed))
+}
+
+/// Lays out every word reached during the test, wrapping at `width`.
+fn review_rows(state: &State, width: usize) -> Vec<Spans<'_>> {
+    let num_typed_words = state.typed_words.len();
+    let num_reached_words = if state.current_word.is_empty() {
+        num_typed_words
+    } else {
+        num_typed_words + 1
+    };
+
+    let mut rows = Vec::new();
+    let mut word_index = 0;
+
+    for line in &state.lines {
+        if word_index >= num_reached_words {
+            break;
+        }
+
+        let mut row = ReviewRow::default();
+        for expected in &line.words {
+            if word_index >= num_reached_words {
+                break;
+            }
+
+            let typed = typed_word(state, word_index);
+            if row.width > 0 && row.width + 1 + word_width(expected, typed) > width {
+                rows.extend(row.into_spans());
+                row = ReviewRow::default();
+            }
+
+            row.push_word(expected, typed);
+            word_index += 1;
+        }
+        rows.extend(row.into_spans());
+    }
+
+    rows
+}
+
+/// Draws the review of the test text, scrolled down by `scroll` rows of words. The scroll
+/// position is clamped to the length of the text.
+pub(super) fn draw_review(
+    frame: &mut Frame<TermionBackend<AlternateScreen<RawTerminal<Stdout>>>>,
+    state: &State,
+    scroll: &mut usize,
+    area: Rect,
+) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    // Subtract 2 for the borders
+    let text_area = layout[0];
+    let width = text_area.width.saturating_sub(2) as usize;
+    let num_visible_rows = text_area.height.saturating_sub(2) as usize / 2;
+
+    let rows = review_rows(state, width);
+    let num_rows = rows.len() / 2;
+    *scroll = min(*scroll, num_rows.saturating_sub(num_visible_rows));
+
+    let title = Span::styled("Review", Style::default().add_modifier(Modifier::BOLD));
+    let paragraph = Paragraph::new(rows)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .scroll(((*scroll * 2) as u16, 0));
+    frame.render_widget(paragraph, text_area);
+
+    let instructions = Paragraph::new(Spans::from(vec![
+        span_correct("Scroll: "),
+        span_default("↑/↓ | "),
+        span_correct("Back: "),
+        span_default("Esc | "),
+        span_correct("Practice mistakes: "),
+        span_default("P | "),
+        span_incorrect("Quit: "),
+        span_default("Ctrl-C"),
+    ]));
+    frame.render_widget(instructions, layout[1]);
+}
@@ -1,17 +1,33 @@
-use std::cmp::max;
+use std::ops::Range;
 
-use itertools::{zip, EitherOrBoth, Itertools};
+use itertools::{EitherOrBoth, Itertools};
 use tui::text::{Span, Spans};
+use unicode_width::UnicodeWidthStr;
 
 use crate::state::State;
 
+use super::layout::TextLayout;
 use super::spans::{span_correct, span_default, span_incorrect, spans_highlight_red};
 
-pub(super) fn word_display_len(s1: &str, s2: &str) -> usize {
-    max(s1.len(), s2.len())
+/// Tabs can't be drawn in a single terminal cell, so they are shown as this character inside
+/// words.
+const TAB_DISPLAY_CHAR: char = '⇥';
+
+const TAB_INDENT: &str = "    ";
+
+pub(super) fn indent_display(indent: &str) -> String {
+    indent.replace('\t', TAB_INDENT)
+}
+
+pub(super) fn display_char(c: char) -> char {
+    if c == '\t' {
+        TAB_DISPLAY_CHAR
+    } else {
+        c
+    }
 }
 
-fn render_word<'a>(
+pub(super) fn render_word<'a>(
     typed_text: &'a str,
     expected_text: &'a str,
     completed_typing: bool,
@@ -20,11 +36,13 @@ fn render_word<'a>(
         .chars()
         .zip_longest(expected_text.chars())
         .map(|entry| match entry {
-            EitherOrBoth::Left(t) => span_incorrect(t.to_string()),
-            EitherOrBoth::Right(e) if completed_typing => span_incorrect(e.to_string()),
-            EitherOrBoth::Right(e) => span_default(e.to_string()),
-            EitherOrBoth::Both(t, e) if t != e => span_incorrect(t.to_string()),
-            EitherOrBoth::Both(t, _) => span_correct(t.to_string()),
+            EitherOrBoth::Left(t) => span_incorrect(display_char(t).to_string()),
+            EitherOrBoth::Right(e) if completed_typing => {
+                span_incorrect(display_char(e).to_string())
+            }
+            EitherOrBoth::Right(e) => span_default(display_char(e).to_string()),
+            EitherOrBoth::Both(t, e) if t != e => span_incorrect(display_char(t).to_string()),
+            EitherOrBoth::Both(t, _) => span_correct(display_char(t).to_string()),
         });
     if typed_text != expected_text && completed_typing {
         spans_highlight_red(spans).collect()
@@ -33,28 +51,51 @@ fn render_word<'a>(
     }
 }
 
-pub(super) fn render_text(state: &State) -> Spans {
-    let all_words = &state.all_words;
-    let typed_words = &state.typed_words;
-    let current_word = &state.current_word;
-
-    let num_typed_words = typed_words.len();
-    let mut spans = Vec::new();
-
-    for (typed, expected) in zip(typed_words.iter(), all_words.iter()) {
-        spans.extend(render_word(typed, expected, true));
-        spans.push(" ".into());
+/// The text typed so far for the word at `index`, which is empty for words not reached yet.
+pub(super) fn typed_word(state: &State, index: usize) -> &str {
+    match state.typed_words.get(index) {
+        Some(typed) => typed,
+        None if index == state.typed_words.len() => &state.current_word,
+        None => "",
     }
+}
+
+/// Renders the rows `rows` of the laid out text, one `Spans` per row.
+pub(super) fn render_text<'a>(
+    state: &'a State,
+    layout: &TextLayout,
+    rows: Range<usize>,
+) -> Vec<Spans<'a>> {
+    let num_typed_words = state.typed_words.len();
+    let words: Vec<&String> = state.words().collect();
+    let rows = rows.start.min(layout.rows.len())..rows.end.min(layout.rows.len());
 
-    spans.extend(render_word(
-        &current_word,
-        &all_words[num_typed_words],
-        false,
-    ));
+    layout.rows[rows]
+        .iter()
+        .map(|row| {
+            let mut spans = vec![span_default(row.indent.clone())];
+            let mut x = row.indent.width();
 
-    spans.push(" ".into());
+            for segment in &row.segments {
+                if segment.x > x {
+                    spans.push(Span::raw(" ".repeat(segment.x - x)));
+                    x = segment.x;
+                }
 
-    spans.push(span_default(all_words[num_typed_words + 1..].join(" ")));
+                let expected = words[segment.word_index];
+                let typed = typed_word(state, segment.word_index);
+                let completed_typing = segment.word_index < num_typed_words;
+                let word_spans = render_word(typed, expected, completed_typing)
+                    .into_iter()
+                    .skip(segment.chars.start)
+                    .take(segment.chars.len());
+                for span in word_spans {
+                    x += span.width();
+                    spans.push(span);
+                }
+            }
 
-    Spans::from(spans)
+            Spans::from(spans)
+        })
+        .collect()
 }
@@ -0,0 +1,27 @@
+use std::cmp::min;
+use std::ops::Range;
+
+use super::layout::TextLayout;
+
+/// The rows of the laid out text that are visible in the text area.
+pub(super) struct Viewport {
+    pub first_row: usize,
+    pub num_rows: usize,
+}
+
+impl Viewport {
+    /// Scrolls so that the row with the cursor is shown `active_row` rows from the top, or as
+    /// close to that as the start of the text allows. This only depends on the current layout,
+    /// so it stays correct when the terminal is resized.
+    pub(super) fn around_cursor(layout: &TextLayout, num_rows: usize, active_row: usize) -> Self {
+        let active_row = min(active_row, num_rows.saturating_sub(1));
+        Self {
+            first_row: layout.cursor.row.saturating_sub(active_row),
+            num_rows,
+        }
+    }
+
+    pub(super) fn rows(&self) -> Range<usize> {
+        self.first_row..self.first_row + self.num_rows
+    }
+}
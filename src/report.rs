@@ -0,0 +1,71 @@
+use crate::error::ApplicationError;
+use crate::fingers::{usage_header, usage_rows, FingerHistory};
+use crate::keyboard::KeyboardLayout;
+use crate::latency::{bigram_latencies, transition_stats};
+use crate::ngram::NgramTimings;
+use crate::opt::CliOptions;
+use crate::storage::{data_dir, FINGER_USAGE_FILE, NGRAM_TIMINGS_FILE};
+
+/// Prints bigram timings and finger transition statistics from the stored n-gram timings, and
+/// finger usage over every test typed on the chosen layout.
+pub fn run_stats_command(limit: usize, opt: &CliOptions) -> Result<(), ApplicationError> {
+    let timings = NgramTimings::load(data_dir(opt).join(NGRAM_TIMINGS_FILE))?;
+    let samples = timings.bigram_samples();
+    if samples.is_empty() {
+        println!("No timings recorded yet, finish a test first");
+        return Ok(());
+    }
+
+    let latencies = bigram_latencies(samples.clone());
+    println!(
+        "Slowest bigrams over the most recent timings ({} of {}):",
+        limit.min(latencies.len()),
+        latencies.len()
+    );
+    println!(
+        "  {:<8} {:>6} {:>9} {:>9}",
+        "bigram", "count", "average", "median"
+    );
+    for latency in latencies.iter().take(limit) {
+        println!(
+            "  {:<8} {:>6} {:>7}ms {:>7}ms",
+            latency.bigram,
+            latency.count,
+            latency.average.as_millis(),
+            latency.median.as_millis()
+        );
+    }
+
+    let layout = KeyboardLayout::load(opt.layout_name())?;
+    println!();
+    println!("Transitions on {}:", layout.name);
+    println!("  {:<18} {:>7} {:>9}", "", "share", "average");
+    for transition in transition_stats(&samples, &layout) {
+        println!(
+            "  {:<18} {:>6.1}% {:>7}ms",
+            transition.transition.name(),
+            transition.share * 100.0,
+            transition.average.as_millis()
+        );
+    }
+
+    let history = FingerHistory::load(data_dir(opt).join(FINGER_USAGE_FILE))?;
+    if let Some(usage) = history.usage(&layout) {
+        println!();
+        println!("Fingers over every test on {}:", layout.name);
+        println!("  {}", usage_header());
+        for row in usage_rows(usage) {
+            println!("  {}", row);
+        }
+        if let Some(share) = usage.same_finger_share() {
+            println!(
+                "  Same finger bigrams: {:.1}% ({} of {})",
+                share * 100.0,
+                usage.same_finger_bigrams,
+                usage.bigram_count
+            );
+        }
+        println!("  Row jumps: {}", usage.row_jumps);
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use itertools::Itertools;
+
+use crate::code::CodeSnippets;
+use crate::dictionary::Dictionary;
+use crate::error::ApplicationError;
+use crate::state::{Line, State};
+use crate::stats::TypedWord;
+
+/// Where the text for a typing test comes from.
+pub enum TextSource {
+    /// An endless stream of random words from a dictionary.
+    Words(Box<Dictionary>),
+    /// A finite snippet of source code, laid out across multiple lines.
+    Code(CodeSnippets),
+    /// A finite text that stays the same from test to test.
+    Fixed(Vec<Line>),
+}
+
+impl TextSource {
+    /// A text made of the words that were mistyped, each appearing once.
+    pub fn from_mistakes(mistyped_words: &[TypedWord]) -> Self {
+        let words = mistyped_words
+            .iter()
+            .map(|word| word.expected.clone())
+            .unique()
+            .collect();
+        TextSource::Fixed(vec![Line {
+            indent: String::new(),
+            words,
+        }])
+    }
+
+    /// Whether the text is random words from word lists, as opposed to a given text or code.
+    pub fn is_random_words(&self) -> bool {
+        matches!(self, TextSource::Words(dictionary) if dictionary.is_random())
+    }
+
+    /// Sets words to mix into the text, if the source is a dictionary.
+    pub fn set_practice_words(&mut self, weighted_words: Vec<(String, f64)>) {
+        if let TextSource::Words(dictionary) = self {
+            dictionary.set_practice_words(weighted_words);
+        }
+    }
+
+    /// Restricts the words to ones made of the given letters, if the source is a dictionary.
+    pub fn set_allowed_letters(&mut self, letters: &[char]) {
+        if let TextSource::Words(dictionary) = self {
+            dictionary.set_allowed_letters(letters);
+        }
+    }
+
+    /// Drills the given n-grams, if the source is a dictionary.
+    pub fn set_ngrams(&mut self, ngrams: Option<Vec<String>>) {
+        if let TextSource::Words(dictionary) = self {
+            dictionary.set_ngrams(ngrams);
+        }
+    }
+
+    /// Mixes made up words in with the real ones, if the source is a dictionary.
+    pub fn set_pseudo_word_ratio(&mut self, ratio: f64) {
+        if let TextSource::Words(dictionary) = self {
+            dictionary.set_pseudo_word_ratio(ratio);
+        }
+    }
+
+    pub fn start_test(&mut self, repeat_text: bool) -> Result<(), ApplicationError> {
+        match self {
+            TextSource::Words(dictionary) => dictionary.start_test(repeat_text),
+            TextSource::Code(snippets) => snippets.start_test(repeat_text)?,
+            TextSource::Fixed(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Makes sure the state has text to show, keeping `num_words_ahead` words past the ones
+    /// already typed if the source is endless.
+    pub fn load_text(&mut self, state: &mut State, num_words_ahead: usize) {
+        match self {
+            TextSource::Words(dictionary) => dictionary.load_words(state, num_words_ahead),
+            TextSource::Code(snippets) => snippets.load_text(state),
+            TextSource::Fixed(lines) => {
+                if state.lines.is_empty() {
+                    state.lines = lines.clone();
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,167 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::opt::InputMode;
+
+/// Gaps between keystrokes longer than this are counted as idle time.
+pub const IDLE_THRESHOLD: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Default)]
 pub struct Counters {
-    pub attempted_word_count: usize,
-    pub correctly_typed_word_count: usize,
+    pub typed_char_count: usize,
+    pub backspace_count: usize,
+    pub word_deletion_count: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keystroke {
+    pub elapsed: Duration,
+    /// The character typed, including the spaces and newlines between words.
+    pub typed: char,
+    /// The character that should have been typed, if any was left in the word.
+    pub expected: Option<char>,
+    /// Whether a backspace or word deletion came right before this keystroke.
+    pub after_correction: bool,
+}
+
+impl Keystroke {
+    pub fn is_correct(&self) -> bool {
+        self.expected == Some(self.typed)
+    }
+}
+
+/// A line of the test text. Its leading indentation is shown but skipped over while typing.
+#[derive(Clone, Debug, Default)]
+pub struct Line {
+    pub indent: String,
+    pub words: Vec<String>,
+}
+
+impl Line {
+    pub fn from_source(source_line: &str) -> Self {
+        let content = source_line.trim_end();
+        let trimmed = content.trim_start();
+        let indent = content[..content.len() - trimmed.len()].to_string();
+        // Each run of spaces separates two words, and any spaces beyond the first stay at the end
+        // of the word before it so that the typed text matches the source exactly
+        let mut words: Vec<String> = Vec::new();
+        for word in trimmed.split(' ') {
+            match words.last_mut() {
+                Some(previous) if word.is_empty() => previous.push(' '),
+                _ if word.is_empty() => {}
+                _ => words.push(word.to_string()),
+            }
+        }
+        Self { indent, words }
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct State {
     pub start_time: Option<Instant>,
+    pub paused_at: Option<Instant>,
+    pub paused_duration: Duration,
+    pub last_activity: Option<Duration>,
+    pub idle_duration: Duration,
     pub quit: bool,
     pub complete: bool,
     pub retry: bool,
-    pub all_words: Vec<String>,
+    pub restart_same_text: bool,
+    pub practice_mistakes: bool,
+    pub lines: Vec<Line>,
     pub typed_words: Vec<String>,
     pub current_word: String,
     pub counters: Counters,
+    pub keystrokes: Vec<Keystroke>,
+    /// Set by a correction until the next keystroke is recorded.
+    pub after_correction: bool,
+    pub input_mode: InputMode,
+}
+
+impl State {
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        self.lines.iter().flat_map(|line| line.words.iter())
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.lines.iter().map(|line| line.words.len()).sum()
+    }
+
+    pub fn expected_word(&self, index: usize) -> Option<&String> {
+        self.words().nth(index)
+    }
+
+    /// The word that is currently being typed, or `None` once the whole text has been typed.
+    pub fn current_expected_word(&self) -> Option<&String> {
+        self.expected_word(self.typed_words.len())
+    }
+
+    /// The character that should be typed next, including the space or newline after a word.
+    /// `None` when the current word has a mistake that needs fixing first.
+    pub fn next_expected_char(&self) -> Option<char> {
+        let expected_word = self.current_expected_word()?;
+        let remaining = expected_word.strip_prefix(self.current_word.as_str())?;
+        match remaining.chars().next() {
+            Some(c) => Some(c),
+            None if self.ends_line(self.typed_words.len()) => Some('\n'),
+            None => Some(' '),
+        }
+    }
+
+    /// Whether the word at `index` is the last one on its line, so that it has to be followed by
+    /// a newline rather than a space.
+    pub fn ends_line(&self, index: usize) -> bool {
+        let mut first_word_index = 0;
+        for (line_index, line) in self.lines.iter().enumerate() {
+            let next_line_start = first_word_index + line.words.len();
+            if index < next_line_start {
+                return index + 1 == next_line_start && line_index + 1 < self.lines.len();
+            }
+            first_word_index = next_line_start;
+        }
+        false
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Time spent typing since the first keystroke, not counting any time spent paused.
+    pub fn elapsed(&self) -> Option<Duration> {
+        let start_time = self.start_time?;
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        now.checked_duration_since(start_time)
+            .map(|elapsed| elapsed.saturating_sub(self.paused_duration))
+    }
+
+    /// Records a keystroke, accumulating the gap since the previous one as idle time if it was
+    /// long enough.
+    pub fn record_activity(&mut self) {
+        if let Some(elapsed) = self.elapsed() {
+            let last_activity = self.last_activity.unwrap_or_default();
+            let gap = elapsed.saturating_sub(last_activity);
+            if gap > IDLE_THRESHOLD {
+                self.idle_duration += gap;
+            }
+            self.last_activity = Some(elapsed);
+        }
+    }
+
+    pub fn record_keystroke(&mut self, typed: char, expected: Option<char>) {
+        if let Some(elapsed) = self.elapsed() {
+            self.keystrokes.push(Keystroke {
+                elapsed,
+                typed,
+                expected,
+                after_correction: std::mem::take(&mut self.after_correction),
+            });
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        match self.paused_at.take() {
+            Some(paused_at) => self.paused_duration += paused_at.elapsed(),
+            None if self.start_time.is_some() => self.paused_at = Some(Instant::now()),
+            None => {}
+        }
+    }
 }
@@ -1,20 +1,248 @@
-use crate::state::Counters;
+use std::ops::AddAssign;
+use std::time::Duration;
 
+use itertools::{EitherOrBoth, Itertools};
+use serde::Serialize;
+
+use crate::fingers::FingerUsage;
+use crate::keyboard::KeyboardLayout;
+use crate::latency::{bigram_latencies, transition_stats, BigramLatency, TransitionStats};
+use crate::ngram::ngram_latencies;
+use crate::state::{Keystroke, State, IDLE_THRESHOLD};
+use crate::storage::serialize_millis;
+
+/// Number of characters in a standardized "word" when computing words per minute.
+const CHARS_PER_WORD: f64 = 5.0;
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct CharCounts {
+    pub correct: usize,
+    pub incorrect: usize,
+    /// Typed characters beyond the end of the expected word.
+    pub extra: usize,
+    /// Expected characters that were never typed. Only counted for submitted words.
+    pub missed: usize,
+}
+
+impl CharCounts {
+    pub fn from_word(typed: &str, expected: &str, completed_typing: bool) -> Self {
+        let mut counts = Self::default();
+        for entry in typed.chars().zip_longest(expected.chars()) {
+            match entry {
+                EitherOrBoth::Both(t, e) if t == e => counts.correct += 1,
+                EitherOrBoth::Both(_, _) => counts.incorrect += 1,
+                EitherOrBoth::Left(_) => counts.extra += 1,
+                EitherOrBoth::Right(_) if completed_typing => counts.missed += 1,
+                EitherOrBoth::Right(_) => {}
+            }
+        }
+        counts
+    }
+}
+
+impl AddAssign for CharCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.correct += other.correct;
+        self.incorrect += other.incorrect;
+        self.extra += other.extra;
+        self.missed += other.missed;
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TypedWord {
+    pub expected: String,
+    pub typed: String,
+}
+
+/// Progress on the word that was still being typed when the test ended.
+#[derive(Serialize)]
+pub struct PartialWord {
+    pub typed: String,
+    pub expected: String,
+    pub correct_char_count: usize,
+    pub has_mistake: bool,
+}
+
+impl PartialWord {
+    fn from_state(state: &State) -> Option<Self> {
+        if state.current_word.is_empty() {
+            return None;
+        }
+
+        let typed = state.current_word.clone();
+        let expected = state.current_expected_word()?.clone();
+        let correct_char_count = typed
+            .chars()
+            .zip(expected.chars())
+            .take_while(|(t, e)| t == e)
+            .count();
+        let has_mistake = !expected.starts_with(&typed);
+
+        Some(Self {
+            typed,
+            expected,
+            correct_char_count,
+            has_mistake,
+        })
+    }
+}
+
+/// Coefficient of variation of the number of characters typed in each second of the test. Lower
+/// is more consistent.
+fn speed_variation(keystrokes: &[Keystroke], duration: Duration) -> Option<f64> {
+    let seconds = duration.as_secs_f64().ceil() as usize;
+    if seconds == 0 || keystrokes.is_empty() {
+        return None;
+    }
+
+    let mut chars_per_second = vec![0usize; seconds];
+    for keystroke in keystrokes {
+        let second = keystroke.elapsed.as_secs() as usize;
+        if let Some(count) = chars_per_second.get_mut(second) {
+            *count += 1;
+        }
+    }
+
+    let n = chars_per_second.len() as f64;
+    let mean = chars_per_second.iter().sum::<usize>() as f64 / n;
+    if mean == 0.0 {
+        return None;
+    }
+    let variance = chars_per_second
+        .iter()
+        .map(|&count| (count as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+
+    Some(variance.sqrt() / mean)
+}
+
+#[derive(Serialize)]
 pub struct Stats {
-    pub accuracy: f64,
-    pub wpm: f64,
+    /// How long the test ran, up to the time limit.
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    /// `None` when no words were attempted, so there is nothing to measure accuracy against.
+    pub accuracy: Option<f64>,
+    /// Speed counting only correctly typed words, plus the correct start of an unfinished word.
+    pub net_wpm: f64,
+    /// Speed counting every character typed, including mistakes and characters later deleted.
+    pub raw_wpm: f64,
+    /// Correct characters per minute.
+    pub cpm: f64,
+    pub consistency: Option<f64>,
+    pub chars: CharCounts,
+    pub attempted_word_count: usize,
+    pub correct_word_count: usize,
+    pub partial_word: Option<PartialWord>,
+    /// Every submitted word, in the order typed.
+    pub words: Vec<TypedWord>,
+    pub mistyped_words: Vec<TypedWord>,
+    pub typed_char_count: usize,
+    #[serde(rename = "idle_time_ms", serialize_with = "serialize_millis")]
+    pub idle_time: Duration,
+    pub backspace_count: usize,
+    pub word_deletion_count: usize,
+    /// Time between consecutive correct keys within words, slowest bigram first.
+    pub bigram_latencies: Vec<BigramLatency>,
+    pub transitions: Vec<TransitionStats>,
+    pub finger_usage: FingerUsage,
 }
 
 impl Stats {
-    pub fn from_counters(counters: &Counters, time_limit_sec: u64) -> Option<Self> {
-        if counters.attempted_word_count > 0 {
-            let accuracy =
-                counters.correctly_typed_word_count as f64 / counters.attempted_word_count as f64;
-            let wpm = counters.correctly_typed_word_count as f64 * (60.0 / time_limit_sec as f64);
+    pub fn from_state(state: &State, time_limit_sec: u64, layout: &KeyboardLayout) -> Self {
+        let counters = &state.counters;
+
+        // Tests with a finite text can be completed before the time runs out
+        let time_limit = Duration::from_secs(time_limit_sec);
+        let duration = state
+            .elapsed()
+            .map_or(time_limit, |elapsed| elapsed.min(time_limit));
 
-            Some(Self { accuracy, wpm })
+        let mut chars = CharCounts::default();
+        let mut correct_word_count = 0;
+        let mut correct_char_count = 0;
+        let mut words = Vec::new();
+        let mut mistyped_words = Vec::new();
+
+        for (typed, expected) in state.typed_words.iter().zip(state.words()) {
+            chars += CharCounts::from_word(typed, expected, true);
+            words.push(TypedWord {
+                expected: expected.clone(),
+                typed: typed.clone(),
+            });
+            if typed == expected {
+                correct_word_count += 1;
+                // Include the space or newline that submitted the word
+                correct_char_count += expected.chars().count() + 1;
+            } else {
+                mistyped_words.push(TypedWord {
+                    expected: expected.clone(),
+                    typed: typed.clone(),
+                });
+            }
+        }
+        let attempted_word_count = state.typed_words.len();
+
+        let partial_word = PartialWord::from_state(state);
+        if let Some(partial) = &partial_word {
+            chars += CharCounts::from_word(&partial.typed, &partial.expected, false);
+            correct_char_count += partial.correct_char_count;
+        }
+
+        // A partially typed word only counts against accuracy once it contains a mistake
+        let attempted = match &partial_word {
+            Some(partial) if partial.has_mistake => attempted_word_count + 1,
+            _ => attempted_word_count,
+        };
+        let accuracy = if attempted > 0 {
+            Some(correct_word_count as f64 / attempted as f64)
         } else {
             None
+        };
+
+        let minutes = duration.as_secs_f64() / 60.0;
+        let per_minute = |count: usize| {
+            if minutes > 0.0 {
+                count as f64 / minutes
+            } else {
+                0.0
+            }
+        };
+        let cpm = per_minute(correct_char_count);
+        let net_wpm = cpm / CHARS_PER_WORD;
+        let raw_wpm = per_minute(counters.typed_char_count) / CHARS_PER_WORD;
+
+        let trailing_idle = duration.saturating_sub(state.last_activity.unwrap_or_default());
+        let idle_time = if trailing_idle > IDLE_THRESHOLD {
+            state.idle_duration + trailing_idle
+        } else {
+            state.idle_duration
+        };
+
+        let bigrams = ngram_latencies(&state.keystrokes, 2);
+
+        Self {
+            duration,
+            accuracy,
+            net_wpm,
+            raw_wpm,
+            cpm,
+            consistency: speed_variation(&state.keystrokes, duration),
+            chars,
+            attempted_word_count,
+            correct_word_count,
+            partial_word,
+            words,
+            mistyped_words,
+            typed_char_count: counters.typed_char_count,
+            idle_time,
+            backspace_count: counters.backspace_count,
+            word_deletion_count: counters.word_deletion_count,
+            bigram_latencies: bigram_latencies(bigrams.clone()),
+            transitions: transition_stats(&bigrams, layout),
+            finger_usage: FingerUsage::from_keystrokes(&state.keystrokes, layout),
         }
     }
 }
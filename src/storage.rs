@@ -0,0 +1,92 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+
+use crate::error::ApplicationError;
+use crate::opt::CliOptions;
+
+pub const TROUBLE_WORDS_FILE: &str = "trouble_words.json";
+pub const NGRAM_TIMINGS_FILE: &str = "ngram_timings.json";
+pub const FINGER_USAGE_FILE: &str = "finger_usage.json";
+pub const RUNS_FILE: &str = "runs.json";
+pub const GOALS_FILE: &str = "goals.json";
+
+/// The directory that data kept across sessions is stored in. Defaults to `grumpytype` inside
+/// `$XDG_DATA_HOME`, or `~/.local/share` when that isn't set.
+pub fn data_dir(opt: &CliOptions) -> PathBuf {
+    if let Some(data_dir) = &opt.data_dir {
+        return data_dir.clone();
+    }
+
+    let base_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".local")
+            .join("share"),
+    };
+    base_dir.join("grumpytype")
+}
+
+/// The directory that word lists made with the `wordlist` command are looked up in.
+pub fn wordlist_dir(opt: &CliOptions) -> PathBuf {
+    data_dir(opt).join("wordlists")
+}
+
+/// Finds a word list given on the command line, falling back to the word list directory when the
+/// path doesn't exist as given, e.g. `--dictionary rust` for `wordlists/rust.txt`.
+pub fn resolve_word_list(opt: &CliOptions, path: &Path) -> PathBuf {
+    if path.exists() {
+        return path.to_path_buf();
+    }
+
+    let dir = wordlist_dir(opt);
+    vec![dir.join(path), dir.join(path).with_extension("txt")]
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Seconds since the Unix epoch.
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Writes a duration as a whole number of milliseconds, for use with `serialize_with`.
+pub fn serialize_millis<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+/// Reads a JSON file, returning `None` if it doesn't exist yet.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, ApplicationError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|e| ApplicationError::DataRead(path.into(), e))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| ApplicationError::DataParse(path.into(), e))
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), ApplicationError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| ApplicationError::DataWrite(dir.into(), e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| ApplicationError::DataSerialize(path.into(), e))?;
+    fs::write(path, contents).map_err(|e| ApplicationError::DataWrite(path.into(), e))
+}
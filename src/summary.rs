@@ -0,0 +1,102 @@
+use std::io;
+
+use termion::color;
+
+use crate::opt::CliOptions;
+use crate::stats::Stats;
+
+/// Width the per-word breakdown is wrapped to.
+const BREAKDOWN_WIDTH: usize = 80;
+
+/// Whether the test reached the goals set with `--goal-wpm` and `--goal-accuracy`. Always true
+/// when no goal was set.
+pub fn goal_met(stats: &Stats, opt: &CliOptions) -> bool {
+    let wpm_met = opt.goal_wpm.is_none_or(|goal| stats.net_wpm >= goal);
+    let accuracy_met = opt.goal_accuracy.is_none_or(|goal| {
+        stats
+            .accuracy
+            .is_some_and(|accuracy| accuracy * 100.0 >= goal)
+    });
+    wpm_met && accuracy_met
+}
+
+fn goal_description(opt: &CliOptions) -> Option<String> {
+    match (opt.goal_wpm, opt.goal_accuracy) {
+        (Some(wpm), Some(accuracy)) => Some(format!("{} WPM at {}% accuracy", wpm, accuracy)),
+        (Some(wpm), None) => Some(format!("{} WPM", wpm)),
+        (None, Some(accuracy)) => Some(format!("{}% accuracy", accuracy)),
+        (None, None) => None,
+    }
+}
+
+/// Lays out every submitted word, with mistyped ones followed by what was typed, e.g.
+/// `quick(quikc)`. Mistakes are shown in red when writing to a terminal.
+fn word_breakdown(stats: &Stats, colored: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in &stats.words {
+        let (entry, width) = if word.typed == word.expected {
+            (word.expected.clone(), word.expected.chars().count())
+        } else {
+            let entry = format!("{}({})", word.expected, word.typed);
+            let width = entry.chars().count();
+            if colored {
+                let red = color::Fg(color::Red);
+                let reset = color::Fg(color::Reset);
+                (format!("{}{}{}", red, entry, reset), width)
+            } else {
+                (entry, width)
+            }
+        };
+
+        if line_width > 0 && line_width + 1 + width > BREAKDOWN_WIDTH {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(&entry);
+        line_width += width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Prints the results of the last test to the normal terminal once the interface has closed.
+pub fn print_summary(stats: &Stats, opt: &CliOptions) {
+    let accuracy = match stats.accuracy {
+        Some(accuracy) => format!("{:.2}%", accuracy * 100.0),
+        None => "-".to_string(),
+    };
+    println!(
+        "WPM: {:.2} | Raw WPM: {:.2} | Accuracy: {} | Words: {}/{} | Time: {:.1}s",
+        stats.net_wpm,
+        stats.raw_wpm,
+        accuracy,
+        stats.correct_word_count,
+        stats.attempted_word_count,
+        stats.duration.as_secs_f64()
+    );
+
+    if let Some(goal) = goal_description(opt) {
+        let result = if goal_met(stats, opt) {
+            "met"
+        } else {
+            "missed"
+        };
+        println!("Goal: {} ({})", goal, result);
+    }
+
+    if opt.word_breakdown {
+        let colored = termion::is_tty(&io::stdout());
+        for line in word_breakdown(stats, colored) {
+            println!("{}", line);
+        }
+    }
+}
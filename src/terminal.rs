@@ -0,0 +1,53 @@
+use std::io::{self, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use crate::error::ApplicationError;
+
+/// Points standard output at the terminal for as long as it's kept, so that the interface can be
+/// drawn when standard output is redirected to a file or pipe. Termion only draws on and switches
+/// the terminal mode of standard output, so it can't be given the terminal directly.
+pub struct TerminalOutput {
+    /// A copy of the redirected standard output, put back when dropped. `None` when standard
+    /// output already is the terminal.
+    saved_stdout: Option<RawFd>,
+}
+
+impl TerminalOutput {
+    pub fn redirect() -> Result<Self, ApplicationError> {
+        let stdout = io::stdout();
+        if termion::is_tty(&stdout) {
+            return Ok(Self { saved_stdout: None });
+        }
+
+        let tty = termion::get_tty().map_err(ApplicationError::TtyOpen)?;
+        let stdout_fd = stdout.as_raw_fd();
+        // Both are open file descriptors. `tty` itself is closed once its copy has taken the place
+        // of standard output.
+        let saved_stdout = unsafe { libc::dup(stdout_fd) };
+        if saved_stdout == -1 {
+            return Err(ApplicationError::StdoutRedirect(io::Error::last_os_error()));
+        }
+        if unsafe { libc::dup2(tty.as_raw_fd(), stdout_fd) } == -1 {
+            let error = io::Error::last_os_error();
+            unsafe { libc::close(saved_stdout) };
+            return Err(ApplicationError::StdoutRedirect(error));
+        }
+
+        Ok(Self {
+            saved_stdout: Some(saved_stdout),
+        })
+    }
+}
+
+impl Drop for TerminalOutput {
+    fn drop(&mut self) {
+        if let Some(saved_stdout) = self.saved_stdout {
+            let mut stdout = io::stdout();
+            let _ = stdout.flush();
+            unsafe {
+                libc::dup2(saved_stdout, stdout.as_raw_fd());
+                libc::close(saved_stdout);
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApplicationError;
+use crate::state::State;
+use crate::storage::{now_timestamp, read_json, write_json};
+
+/// A trouble word is dropped from the list once it has been typed correctly this many times in a
+/// row.
+const MASTERED_STREAK: u32 = 3;
+
+/// A trouble word comes up half as often for every this many days since it was last missed.
+const RECENCY_HALF_LIFE_DAYS: f64 = 7.0;
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TroubleWord {
+    pub word: String,
+    pub miss_count: u32,
+    /// Seconds since the Unix epoch.
+    pub last_missed: u64,
+    /// How many times the word has been typed correctly since it was last missed.
+    pub correct_streak: u32,
+}
+
+impl TroubleWord {
+    /// How often the word should come up in practice at time `now`. Each correct attempt in a row
+    /// halves it, so the gaps between repetitions grow as the word is learned, and it fades as the
+    /// last miss gets older.
+    pub fn weight(&self, now: u64) -> f64 {
+        let age_days = now.saturating_sub(self.last_missed) as f64 / SECONDS_PER_DAY;
+        let recency = 0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+        self.miss_count as f64 / 2f64.powi(self.correct_streak as i32) * recency
+    }
+}
+
+/// Words that have been mistyped, kept across sessions.
+pub struct TroubleWords {
+    path: PathBuf,
+    words: Vec<TroubleWord>,
+}
+
+impl TroubleWords {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApplicationError> {
+        let path = path.as_ref().to_path_buf();
+        let words = read_json(&path)?.unwrap_or_default();
+        Ok(Self { path, words })
+    }
+
+    pub fn save(&self) -> Result<(), ApplicationError> {
+        write_json(&self.path, &self.words)
+    }
+
+    /// Updates the list with every word submitted during a test.
+    pub fn record_test(&mut self, state: &State) {
+        let now = now_timestamp();
+
+        // Only count each word once per test, so a single slip doesn't dominate the list. A word
+        // counts as missed if any of its occurrences was mistyped.
+        let mut results: Vec<(&String, bool)> = Vec::new();
+        let mut indexes: HashMap<&String, usize> = HashMap::new();
+        for (typed, expected) in state.typed_words.iter().zip(state.words()) {
+            let missed = typed != expected;
+            match indexes.get(expected) {
+                Some(&index) => results[index].1 |= missed,
+                None => {
+                    indexes.insert(expected, results.len());
+                    results.push((expected, missed));
+                }
+            }
+        }
+
+        for (expected, missed) in results {
+            let existing = self.words.iter_mut().find(|word| &word.word == expected);
+            match existing {
+                Some(word) if !missed => word.correct_streak += 1,
+                Some(word) => {
+                    word.miss_count += 1;
+                    word.last_missed = now;
+                    word.correct_streak = 0;
+                }
+                None if missed => self.words.push(TroubleWord {
+                    word: expected.clone(),
+                    miss_count: 1,
+                    last_missed: now,
+                    correct_streak: 0,
+                }),
+                None => {}
+            }
+        }
+
+        self.words
+            .retain(|word| word.correct_streak < MASTERED_STREAK);
+    }
+
+    /// Each trouble word paired with how likely it should be to come up in practice.
+    pub fn weighted_words(&self) -> Vec<(String, f64)> {
+        let now = now_timestamp();
+        self.words
+            .iter()
+            .map(|word| (word.word.clone(), word.weight(now)))
+            .collect()
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+use crate::dictionary::read_word_list;
+use crate::error::ApplicationError;
+use crate::opt::{CliOptions, WordlistCommand};
+use crate::storage::{resolve_word_list, wordlist_dir};
+
+/// Width of the longest bar in the length histogram.
+const HISTOGRAM_WIDTH: usize = 40;
+
+pub fn run_wordlist_command(
+    command: &WordlistCommand,
+    opt: &CliOptions,
+) -> Result<(), ApplicationError> {
+    match command {
+        WordlistCommand::List => list(opt),
+        WordlistCommand::Stats { path } => stats(&resolve_word_list(opt, path)),
+        WordlistCommand::Import {
+            corpus,
+            output,
+            limit,
+            min_count,
+            min_word_len,
+            max_word_len,
+        } => import(
+            corpus,
+            output,
+            *limit,
+            *min_count,
+            *min_word_len,
+            *max_word_len,
+        ),
+        WordlistCommand::Filter {
+            input,
+            output,
+            letters,
+            min_word_len,
+            max_word_len,
+        } => filter(
+            &resolve_word_list(opt, input),
+            output,
+            letters.as_deref(),
+            *min_word_len,
+            *max_word_len,
+        ),
+    }
+}
+
+fn word_list_files(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "txt"))
+        .sorted()
+        .collect()
+}
+
+fn print_word_list_summary(path: &Path) {
+    match read_word_list(path) {
+        Ok(words) => println!("  {} ({} words)", path.display(), words.len()),
+        Err(e) => println!("  {} ({})", path.display(), e),
+    }
+}
+
+fn list(opt: &CliOptions) -> Result<(), ApplicationError> {
+    println!("Selected:");
+    for source in &opt.dictionaries {
+        print_word_list_summary(&resolve_word_list(opt, &source.path));
+    }
+
+    let dir = wordlist_dir(opt);
+    println!("In {}:", dir.display());
+    let files = word_list_files(&dir);
+    if files.is_empty() {
+        println!("  none");
+    }
+    for path in files {
+        print_word_list_summary(&path);
+    }
+    Ok(())
+}
+
+fn stats(path: &Path) -> Result<(), ApplicationError> {
+    let words = read_word_list(path)?;
+    println!("{}: {} words", path.display(), words.len());
+
+    let mut lengths = BTreeMap::new();
+    let mut letters = HashMap::new();
+    let mut letter_count = 0;
+    for word in &words {
+        *lengths.entry(word.chars().count()).or_insert(0) += 1;
+        for c in word.chars().flat_map(char::to_lowercase) {
+            *letters.entry(c).or_insert(0) += 1;
+            letter_count += 1;
+        }
+    }
+
+    println!();
+    println!("Word lengths:");
+    let most_common_length = lengths.values().copied().max().unwrap_or_default();
+    for (length, count) in &lengths {
+        let bar_len = (count * HISTOGRAM_WIDTH).div_ceil(most_common_length);
+        println!("  {:>3} {:>7} {}", length, count, "#".repeat(bar_len));
+    }
+
+    println!();
+    println!("Letter frequencies:");
+    let letters = letters
+        .into_iter()
+        .sorted_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    for (letter, count) in letters {
+        println!(
+            "  {} {:>7} {:>6.2}%",
+            letter,
+            count,
+            count as f64 / letter_count as f64 * 100.0
+        );
+    }
+    Ok(())
+}
+
+fn write_word_list(path: &Path, header: &str, words: &[String]) -> Result<(), ApplicationError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| ApplicationError::WordListWrite(dir.into(), e))?;
+    }
+
+    let contents = format!("# {}\n{}\n", header, words.join("\n"));
+    fs::write(path, contents).map_err(|e| ApplicationError::WordListWrite(path.into(), e))?;
+    println!("Wrote {} words to {}", words.len(), path.display());
+    Ok(())
+}
+
+/// Builds a word list from the words in a text, most frequent first.
+fn import(
+    corpus: &Path,
+    output: &Path,
+    limit: Option<usize>,
+    min_count: usize,
+    min_word_len: usize,
+    max_word_len: usize,
+) -> Result<(), ApplicationError> {
+    if min_word_len > max_word_len {
+        return Err(ApplicationError::WordLengthBounds(
+            min_word_len,
+            max_word_len,
+        ));
+    }
+
+    let text =
+        fs::read_to_string(corpus).map_err(|e| ApplicationError::CorpusLoad(corpus.into(), e))?;
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in text.split(|c: char| !c.is_alphabetic()) {
+        let len = word.chars().count();
+        if min_word_len <= len && len <= max_word_len {
+            *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+        }
+    }
+
+    let words: Vec<String> = counts
+        .into_iter()
+        .filter(|&(_, count)| count >= min_count)
+        .sorted_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)))
+        .map(|(word, _)| word)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    if words.is_empty() {
+        return Err(ApplicationError::DictionaryEmpty(
+            corpus.into(),
+            min_word_len,
+            max_word_len,
+        ));
+    }
+
+    let header = format!("Imported from {}, most frequent first", corpus.display());
+    write_word_list(output, &header, &words)
+}
+
+/// Writes the words of a list that only use the given letters and fit the length bounds.
+fn filter(
+    input: &Path,
+    output: &Path,
+    letters: Option<&str>,
+    min_word_len: usize,
+    max_word_len: usize,
+) -> Result<(), ApplicationError> {
+    if min_word_len > max_word_len {
+        return Err(ApplicationError::WordLengthBounds(
+            min_word_len,
+            max_word_len,
+        ));
+    }
+
+    let words: Vec<String> = read_word_list(input)?
+        .into_iter()
+        .filter(|word| {
+            let len = word.chars().count();
+            min_word_len <= len && len <= max_word_len
+        })
+        .collect();
+    if words.is_empty() {
+        return Err(ApplicationError::DictionaryEmpty(
+            input.into(),
+            min_word_len,
+            max_word_len,
+        ));
+    }
+
+    let words = match letters {
+        Some(letters) => {
+            let allowed: HashSet<char> = letters.chars().collect();
+            let words: Vec<String> = words
+                .into_iter()
+                .filter(|word| word.chars().all(|c| allowed.contains(&c)))
+                .collect();
+            if words.is_empty() {
+                return Err(ApplicationError::DictionaryLettersEmpty(
+                    input.into(),
+                    letters.to_string(),
+                ));
+            }
+            words
+        }
+        None => words,
+    };
+
+    let header = match letters {
+        Some(letters) => format!(
+            "Filtered from {} to letters {} and lengths {}-{}",
+            input.display(),
+            letters,
+            min_word_len,
+            max_word_len
+        ),
+        None => format!(
+            "Filtered from {} to lengths {}-{}",
+            input.display(),
+            min_word_len,
+            max_word_len
+        ),
+    };
+    write_word_list(output, &header, &words)
+}